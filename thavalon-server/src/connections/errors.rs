@@ -5,7 +5,9 @@ use crate::connections::account_handlers::{
     DuplicateAccountRejection, EmailVerificationRejection, InvalidLoginRejection,
     PasswordInsecureRejection, ValidationRejection,
 };
-use crate::connections::game_handlers::{NonexistentGameRejection, UnverifiedEmailRejection};
+use crate::connections::game_handlers::{
+    InvalidBodyRejection, NonexistentGameRejection, UnverifiedEmailRejection,
+};
 use serde::Serialize;
 use std::convert::Infallible;
 use warp::{http::StatusCode, reject::InvalidHeader, Rejection, Reply};
@@ -27,6 +29,7 @@ enum ErrorCode {
     InvalidAccountVerification,
     AccountNotVerified,
     NoActiveGame,
+    InvalidBody,
     Unknown = 255,
 }
 
@@ -79,6 +82,10 @@ pub async fn recover_errors(err: Rejection) -> Result<impl Reply, Infallible> {
         http_response_code = StatusCode::NOT_FOUND;
         error_message = "No active game found matching the supplied friend code.".to_string();
         error_code = ErrorCode::NoActiveGame;
+    } else if let Some(InvalidBodyRejection) = err.find() {
+        http_response_code = StatusCode::BAD_REQUEST;
+        error_message = "The request body is not valid for this endpoint.".to_string();
+        error_code = ErrorCode::InvalidBody;
     }
 
     if error_code == ErrorCode::Unknown {
@@ -1,7 +1,8 @@
 //! Module for all game-related REST endpoint handlers. This module also handles
 //! all websocket related functions.
 
-
+use crate::database::games::DatabaseGame;
+use crate::game::Ruleset;
 use crate::lobby::{Lobby, LobbyChannel, LobbyCommand, LobbyResponse};
 
 use lazy_static::lazy_static;
@@ -24,6 +25,13 @@ use std::sync::{Arc, Mutex};
 /// Type used for a global GameCollection of all active games.
 pub type GameCollection = Arc<Mutex<HashMap<String, LobbyChannel>>>;
 
+/// Deserializeable request to create a new game. The body is optional, and an empty body uses the default rules.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CreateGameRequest {
+    ruleset: Ruleset,
+}
+
 /// Serializeable response for a new game. Contains the friend code to join the game.
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -51,6 +59,11 @@ pub struct JoinGameResponse {
 pub struct UnverifiedEmailRejection;
 impl Reject for UnverifiedEmailRejection {}
 
+/// Rejection for when a request body is not valid JSON for the endpoint.
+#[derive(Debug)]
+pub struct InvalidBodyRejection;
+impl Reject for InvalidBodyRejection {}
+
 /// Rejection for when a player attempts to join a nonexistant game.
 #[derive(Debug)]
 pub struct NonexistentGameRejection;
@@ -65,6 +78,7 @@ lazy_static! {
 ///
 /// # Arguments
 ///
+/// * `info` - The house rules for the new game.
 /// * `player_id` - The Player ID of the game creator.
 /// * `game_collection` - The global store of active games.
 ///
@@ -73,6 +87,7 @@ lazy_static! {
 /// * `NewGameResponse` on success.
 /// * `UnverifiedEmailRejection` if the player's email isn't verified.
 pub async fn create_game(
+    info: CreateGameRequest,
     player_id: String,
     game_collection: GameCollection,
 ) -> Result<impl Reply, Rejection> {
@@ -92,7 +107,7 @@ pub async fn create_game(
 
     // Create a new game and add the player.
     let (end_game_tx, end_game_rx) = oneshot::channel();
-    let mut lobby_channel = Lobby::new(end_game_tx).await;
+    let mut lobby_channel = Lobby::new(end_game_tx, player_id.clone(), info.ruleset).await;
     let (oneshot_tx, oneshot_rx) = oneshot::channel();
 
     // TODO: Error handling here.
@@ -128,6 +143,34 @@ pub async fn create_game(
     Ok(reply::json(&response))
 }
 
+/// Restores every game that was in progress when the server last stopped, so players can reconnect to them.
+///
+/// # Arguments
+///
+/// * `game_collection` - The global store of active games.
+pub async fn restore_games(game_collection: GameCollection) {
+    for database_game in DatabaseGame::load_in_progress().await {
+        let (end_game_tx, end_game_rx) = oneshot::channel();
+        let friend_code = database_game.get_friend_code().clone();
+        let lobby_channel = match Lobby::restore(end_game_tx, database_game).await {
+            Some(channel) => channel,
+            None => continue,
+        };
+
+        game_collection
+            .lock()
+            .unwrap()
+            .insert(friend_code.clone(), lobby_channel.clone());
+        tokio::spawn(monitor_lobby_task(
+            lobby_channel,
+            end_game_rx,
+            friend_code.clone(),
+            game_collection.clone(),
+        ));
+        log::info!("Restored game {}.", friend_code);
+    }
+}
+
 /// Adds a player to an existing game
 ///
 /// # Arguments
@@ -144,9 +187,54 @@ pub async fn join_game(
     info: JoinGameRequest,
     player_id: String,
     game_collection: GameCollection,
+) -> Result<impl Reply, Rejection> {
+    log::info!("Player {} is joining game {}.", player_id, info.friend_code);
+    let command = LobbyCommand::AddPlayer {
+        player_id: player_id.clone(),
+        display_name: info.display_name.clone(),
+    };
+    join_lobby(info, command, player_id, game_collection).await
+}
+
+/// Adds a spectator to an existing game. Spectators may join at any point before the game is over, and only
+/// receive public game messages.
+///
+/// # Arguments
+///
+/// * `info` - The info required to join the game.
+/// * `player_id` - The ID of the spectating player.
+/// * `game_collection` - The global collection of active games.
+///
+/// # Returns
+///
+/// * `JoinGameResponse` on success
+/// * `NonexistentGameRejection` if the game doesn't exist
+pub async fn spectate_game(
+    info: JoinGameRequest,
+    player_id: String,
+    game_collection: GameCollection,
+) -> Result<impl Reply, Rejection> {
+    log::info!(
+        "Player {} is spectating game {}.",
+        player_id,
+        info.friend_code
+    );
+    let command = LobbyCommand::AddSpectator {
+        player_id: player_id.clone(),
+        display_name: info.display_name.clone(),
+    };
+    join_lobby(info, command, player_id, game_collection).await
+}
+
+/// Helper for joining a lobby as either a player or a spectator. Sends `command` to the lobby and responds with the
+/// socket URL for the new client.
+async fn join_lobby(
+    info: JoinGameRequest,
+    command: LobbyCommand,
+    player_id: String,
+    game_collection: GameCollection,
 ) -> Result<impl Reply, Rejection> {
     let friend_code = &info.friend_code.to_uppercase();
-    log::info!("Player {} is joining game {}.", player_id, friend_code);
     let mut lobby_channel = match game_collection.lock().unwrap().get(friend_code) {
         Some(channel) => channel.clone(),
         None => {
@@ -159,15 +247,7 @@ pub async fn join_game(
 
     // TODO: Figure out if this needs error handling.
     // Can't use .unwrap() here since SendError doesn't implement Debug
-    let _ = lobby_channel
-        .send((
-            LobbyCommand::AddPlayer {
-                player_id: player_id.clone(),
-                display_name: info.display_name.clone(),
-            },
-            Some(oneshot_tx),
-        ))
-        .await;
+    let _ = lobby_channel.send((command, Some(oneshot_tx))).await;
 
     let client_id = match oneshot_rx.await.unwrap() {
         LobbyResponse::JoinGame(result) => match result {
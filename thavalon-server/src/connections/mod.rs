@@ -33,6 +33,7 @@ pub async fn serve_connections() {
     let token_manager = TokenManager::new();
 
     let game_collection: GameCollection = Arc::new(Mutex::new(HashMap::new()));
+    game_handlers::restore_games(game_collection.clone()).await;
 
     // TEST ROUTES
     let path_test = warp::path("hi").map(|| "Hello, World!");
@@ -81,6 +82,7 @@ pub async fn serve_connections() {
 
     // Game routes
     let create_game_route = warp::path!("add" / "game")
+        .and(create_game_body())
         .and(authorize_request(&token_manager))
         .and(with_game_collection(game_collection.clone()))
         .and_then(game_handlers::create_game);
@@ -91,6 +93,12 @@ pub async fn serve_connections() {
         .and(with_game_collection(game_collection.clone()))
         .and_then(game_handlers::join_game);
 
+    let spectate_game_route = warp::path!("spectate" / "game")
+        .and(body::json())
+        .and(authorize_request(&token_manager))
+        .and(with_game_collection(game_collection.clone()))
+        .and_then(game_handlers::spectate_game);
+
     let ws_route = warp::path("ws")
         .and(warp::ws())
         .and(warp::path::param())
@@ -111,7 +119,8 @@ pub async fn serve_connections() {
             .or(refresh_jwt_route)
             .or(logout_route)
             .or(create_game_route)
-            .or(join_game_route),
+            .or(join_game_route)
+            .or(spectate_game_route),
     );
     let delete_routes = warp::delete().and(delete_user_route);
     let put_routes = warp::put().and(update_user_route.or(verify_account_route));
@@ -177,6 +186,21 @@ async fn authorize_user(header: String, token_manager: TokenManager) -> Result<S
     Ok(player_id)
 }
 
+/// Extracts the body of a create game request. An empty body uses the default rules, but a body that doesn't parse
+/// is rejected rather than silently falling back to them.
+fn create_game_body(
+) -> impl Filter<Extract = (game_handlers::CreateGameRequest,), Error = Rejection> + Clone {
+    body::bytes().and_then(|bytes: warp::hyper::body::Bytes| async move {
+        if bytes.is_empty() {
+            return Ok(game_handlers::CreateGameRequest::default());
+        }
+        serde_json::from_slice(&bytes).map_err(|e| {
+            log::info!("Invalid create game request: {}.", e);
+            reject::custom(game_handlers::InvalidBodyRejection)
+        })
+    })
+}
+
 /// Moves a token_store reference into downstream filters.
 /// Used to add and validate refresh tokens and JWTs.
 ///
@@ -1,10 +1,13 @@
 //! Game collection related functions and structs
+use super::DatabaseHistory;
 use crate::database::get_database;
+use crate::game::history::GameHistory;
 use crate::utils;
 
 use std::collections::{HashMap, HashSet};
 
 use chrono::Utc;
+use futures::StreamExt;
 use mongodb::{
     bson::{self, doc, oid::ObjectId, Document},
     Collection,
@@ -68,15 +71,25 @@ pub struct DatabaseGame {
     start_time: Option<i64>,
     end_time: Option<i64>,
     snapshot_id: Option<String>,
+    /// Player ID of the player who created the game. Only the host can manage the game, for example adding bots.
+    #[serde(default)]
+    host: String,
+    /// Setup and events of the game, written by [`DatabaseHistory`] while the game runs.
+    #[serde(default)]
+    history: Option<GameHistory>,
 }
 
 impl DatabaseGame {
     /// Creates a new DB game entry and returns a DatabaseGame
     ///
+    /// # Arguments
+    ///
+    /// * `host` - The player ID of the player creating the game
+    ///
     /// # Returns
     ///
     /// * `DatabaseGame` on success. `GameError::CreationError` on failure.
-    pub async fn new() -> Result<Self, DBGameError> {
+    pub async fn new(host: String) -> Result<Self, DBGameError> {
         log::info!("Creating a new database game.");
         let collection = DatabaseGame::get_collection().await;
         let _id: ObjectId = match collection.insert_one(doc! {}, None).await {
@@ -101,6 +114,8 @@ impl DatabaseGame {
             start_time: None,
             end_time: None,
             snapshot_id: None,
+            host,
+            history: None,
         };
 
         collection
@@ -116,6 +131,40 @@ impl DatabaseGame {
         Ok(game)
     }
 
+    /// Loads every game that was in progress with a recorded history, so the games can be resumed after a
+    /// server restart.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<DatabaseGame>` of in-progress games. Games that can't be read are logged and skipped.
+    pub async fn load_in_progress() -> Vec<Self> {
+        log::info!("Loading in-progress games from the database.");
+        let collection = DatabaseGame::get_collection().await;
+        let filter = doc! {
+            "status": bson::to_bson(&DBGameStatus::InProgress).unwrap(),
+            "history": { "$ne": null },
+        };
+        let mut cursor = match collection.find(filter, None).await {
+            Ok(cursor) => cursor,
+            Err(e) => {
+                log::error!("ERROR: failed to load in-progress games. {}.", e);
+                return Vec::new();
+            }
+        };
+
+        let mut games = Vec::new();
+        while let Some(result) = cursor.next().await {
+            match result.map(bson::from_document::<DatabaseGame>) {
+                Ok(Ok(game)) => games.push(game),
+                Ok(Err(e)) => log::error!("ERROR: failed to deserialize in-progress game. {}.", e),
+                Err(e) => log::error!("ERROR: failed to read in-progress game. {}.", e),
+            }
+        }
+
+        log::info!("Loaded {} in-progress games.", games.len());
+        games
+    }
+
     /// Starts the database game, updating the DB as needed. Once started,
     /// no players may be added or removed.
     ///
@@ -207,7 +256,8 @@ impl DatabaseGame {
         let update_doc = doc! {
             "$set": {
                 "players": bson::to_bson(&self.players).unwrap(),
-                "display_names": bson::to_bson(&self.display_names).unwrap()
+                "display_names": bson::to_bson(&self.display_names).unwrap(),
+                "players_to_display_names": bson::to_bson(&self.players_to_display_names).unwrap()
             }
         };
 
@@ -260,7 +310,8 @@ impl DatabaseGame {
         let update_doc = doc! {
             "$set": {
                 "players": bson::to_bson(&self.players).unwrap(),
-                "display_names": bson::to_bson(&self.display_names).unwrap()
+                "display_names": bson::to_bson(&self.display_names).unwrap(),
+                "players_to_display_names": bson::to_bson(&self.players_to_display_names).unwrap()
             }
         };
 
@@ -268,12 +319,87 @@ impl DatabaseGame {
         Ok(Some(display_name))
     }
 
+    /// Hands an in-progress game's seat from one player to another. The seat keeps its display name, since the game
+    /// identifies players by display name. Players can only be replaced if the game status is `InProgress`.
+    ///
+    /// # Arguments
+    ///
+    /// * `old_player_id` - The player ID giving up their seat
+    /// * `new_player_id` - The player ID taking over the seat
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The seat's display name on success
+    /// * `DBGameError::InvalidStateError` if the game state isn't `InProgress` or the old player isn't in the game
+    /// * `DBGameError::UpdateError` if a DB update fails
+    pub async fn replace_player(
+        &mut self,
+        old_player_id: &str,
+        new_player_id: String,
+    ) -> Result<String, DBGameError> {
+        log::info!(
+            "Replacing player {} with {} in game {}.",
+            old_player_id,
+            new_player_id,
+            self._id
+        );
+        if self.status != DBGameStatus::InProgress {
+            log::error!(
+                "ERROR: attempted to replace player {} in game {} while in state {:?}. 
+            Players may only be replaced during the InProgress phase.",
+                old_player_id,
+                self._id,
+                self.status
+            );
+            return Err(DBGameError::InvalidStateError);
+        }
+
+        let display_name = match self.players_to_display_names.remove(old_player_id) {
+            Some(name) => name,
+            None => {
+                log::warn!(
+                    "Tried to replace nonexistant player {} in game {}.",
+                    old_player_id,
+                    self._id
+                );
+                return Err(DBGameError::InvalidStateError);
+            }
+        };
+        self.players.remove(old_player_id);
+        self.players.insert(new_player_id.clone());
+        // Whoever takes over the host's seat takes over hosting too.
+        if self.host == old_player_id {
+            self.host = new_player_id.clone();
+        }
+        self.players_to_display_names
+            .insert(new_player_id, display_name.clone());
+        let update_doc = doc! {
+            "$set": {
+                "players": bson::to_bson(&self.players).unwrap(),
+                "players_to_display_names": bson::to_bson(&self.players_to_display_names).unwrap(),
+                "host": bson::to_bson(&self.host).unwrap()
+            }
+        };
+
+        self.update_db(update_doc).await?;
+        Ok(display_name)
+    }
+
+    /// Creates a recorder that saves the history of this game as it's played.
+    ///
+    /// # Returns
+    ///
+    /// * `DatabaseHistory` for this game
+    pub fn history_recorder(&self) -> DatabaseHistory {
+        DatabaseHistory::new(self._id.clone())
+    }
+
     /// Helper function to get a handle to the game collection.
     ///
     /// # Returns
     ///
     /// `Collection` of Thavalon DB games
-    async fn get_collection() -> Collection {
+    pub(super) async fn get_collection() -> Collection {
         get_database().await.collection(GAME_COLLECTION)
     }
 
@@ -300,6 +426,33 @@ impl DatabaseGame {
         Ok(())
     }
 
+    /// Getter for the players_to_display_names field.
+    ///
+    /// # Returns
+    ///
+    /// A `HashMap` from player IDs to display names
+    pub fn get_players_to_display_names(&self) -> &HashMap<String, String> {
+        &self.players_to_display_names
+    }
+
+    /// Getter for the host field.
+    ///
+    /// # Returns
+    ///
+    /// The player ID of the game's host
+    pub fn get_host(&self) -> &String {
+        &self.host
+    }
+
+    /// Takes the game history recorded so far, if any. This is used to resume a game after a server restart.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(GameHistory)` if the game has a recorded history, `None` otherwise
+    pub fn take_history(&mut self) -> Option<GameHistory> {
+        self.history.take()
+    }
+
     /// Getter for the friend_code field.
     ///
     /// # Returns
@@ -0,0 +1,66 @@
+//! Persists the event-sourced history of a running game into its database game entry.
+use super::DatabaseGame;
+use crate::game::history::{GameEvent, GameHistory, GameSetup, Recorder};
+use crate::game::messages::GameReveal;
+
+use async_trait::async_trait;
+use mongodb::bson::{self, doc, oid::ObjectId, Document};
+
+/// A [`Recorder`] that writes a game's history to the `history` field of its database game, one event at a time.
+pub struct DatabaseHistory {
+    _id: ObjectId,
+}
+
+impl DatabaseHistory {
+    /// Creates a recorder for the database game with the given ID.
+    pub fn new(_id: ObjectId) -> DatabaseHistory {
+        DatabaseHistory { _id }
+    }
+
+    /// Applies an update document to the database game, logging any failures. A game whose history can't be saved
+    /// can still be played, so errors are not fatal.
+    async fn update(&self, update_doc: Document) {
+        let collection = DatabaseGame::get_collection().await;
+        if let Err(e) = collection
+            .update_one(doc! {"_id": &self._id}, update_doc, None)
+            .await
+        {
+            log::error!(
+                "ERROR: failed to update history for game {}. {}.",
+                self._id,
+                e
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl Recorder for DatabaseHistory {
+    async fn start(&mut self, setup: &GameSetup) {
+        log::info!("Recording history for DB game {}.", self._id);
+        let update_doc = doc! {
+            "$set": {
+                "history": bson::to_bson(&GameHistory::new(setup.clone())).unwrap(),
+            }
+        };
+        self.update(update_doc).await;
+    }
+
+    async fn record(&mut self, event: &GameEvent) {
+        let update_doc = doc! {
+            "$push": {
+                "history.events": bson::to_bson(event).unwrap(),
+            }
+        };
+        self.update(update_doc).await;
+    }
+
+    async fn finish(&mut self, reveal: &GameReveal) {
+        let update_doc = doc! {
+            "$set": {
+                "history.reveal": bson::to_bson(reveal).unwrap(),
+            }
+        };
+        self.update(update_doc).await;
+    }
+}
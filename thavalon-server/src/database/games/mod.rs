@@ -1,4 +1,6 @@
 //! Database module for all game related collections and functions
 
 mod db_game;
+mod db_history;
 pub use db_game::*;
+pub use db_history::DatabaseHistory;
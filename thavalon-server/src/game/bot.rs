@@ -0,0 +1,494 @@
+//! Computer-controlled players. A [`Bot`] reads the same [`Message`] stream a human player would and responds with
+//! [`Action`]s, so it can fill a seat in any game.
+
+use std::collections::{HashMap, HashSet};
+
+use rand::prelude::*;
+use tokio::sync::mpsc;
+
+use super::messages::{Accusation, Action, GameActionError, Message};
+use super::role::{PriorityTarget, Role, RoleDetails, Team};
+use super::{Card, GameRng, MissionNumber};
+
+/// How much more suspicious a player becomes when they're on a failed mission.
+const FAILED_MISSION_SUSPICION: i32 = 2;
+
+/// How much Merlin suspects the players they see.
+const SEEN_EVIL_SUSPICION: i32 = 10;
+
+/// A bot player using simple heuristics. Good bots send players they trust and vote against proposals with
+/// suspicious players on them. Evil bots try to get their team on missions and usually fail them.
+pub struct Bot {
+    name: String,
+    rng: GameRng,
+    role: Option<RoleDetails>,
+    players: Vec<String>,
+    /// How suspicious each player is of being Evil. Players not in the map have a suspicion of 0.
+    suspicion: HashMap<String, i32>,
+    mission: MissionNumber,
+    mission_size: usize,
+    /// Proposals that will be voted on next. On mission 1, there are two of these.
+    proposals: Vec<HashSet<String>>,
+    /// The players on the mission that is going or most recently went
+    mission_players: HashSet<String>,
+    /// Arthur, if they've declared. Arthur can't go on missions until mission 5 once they declare.
+    declared_arthur: Option<String>,
+    /// Actions to take as soon as the bot can, either when it starts after being restored mid-game or when a paused
+    /// game resumes.
+    pending: Vec<Action>,
+    /// The most recent actions the bot took, so it can take them again if the game was paused and rejected them.
+    last_actions: Vec<Action>,
+}
+
+impl Bot {
+    /// Create a bot called `name`. Its decisions are random, but fully determined by `seed`.
+    pub fn new(name: String, seed: u32) -> Bot {
+        Bot {
+            name,
+            rng: GameRng::seed_from_u64(seed.into()),
+            role: None,
+            players: Vec::new(),
+            suspicion: HashMap::new(),
+            mission: 1,
+            mission_size: 0,
+            proposals: Vec::new(),
+            mission_players: HashSet::new(),
+            declared_arthur: None,
+            pending: Vec::new(),
+            last_actions: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Catch the bot up on `messages` it missed, for example because the game was restored after a server restart.
+    /// The bot only acts on the most recent message that needed a response, since it already responded to the others.
+    /// Repeating that response is harmless, because the game rejects actions that were already taken.
+    pub fn restore<I: IntoIterator<Item = Message>>(&mut self, messages: I) {
+        for message in messages {
+            let actions = self.on_message(&message);
+            if !actions.is_empty() {
+                self.pending = actions;
+            }
+        }
+    }
+
+    /// Update the bot's view of the game with a message, returning any actions it takes in response.
+    pub fn on_message(&mut self, message: &Message) -> Vec<Action> {
+        let actions = self.respond(message);
+        if !actions.is_empty() {
+            self.last_actions = actions.clone();
+        }
+        actions
+    }
+
+    fn respond(&mut self, message: &Message) -> Vec<Action> {
+        match message {
+            Message::ProposalOrder(players) => {
+                self.players = players.clone();
+                vec![]
+            }
+            Message::RoleInformation { details } => {
+                self.on_role_information(details);
+                vec![]
+            }
+            Message::NextProposal {
+                proposer,
+                mission,
+                mission_size,
+                ..
+            } => {
+                if *mission != self.mission {
+                    self.proposals.clear();
+                }
+                self.mission = *mission;
+                self.mission_size = *mission_size;
+                if proposer == &self.name {
+                    vec![Action::Propose {
+                        players: self.choose_proposal(),
+                    }]
+                } else {
+                    vec![]
+                }
+            }
+            Message::ProposalMade { players, .. } => {
+                self.proposals.push(players.clone());
+                vec![]
+            }
+            Message::CommenceVoting => self.vote(),
+            Message::VotingResults { .. } => {
+                self.proposals.clear();
+                vec![]
+            }
+            Message::MissionGoing { players, .. } => {
+                self.mission_players = players.clone();
+                if players.contains(&self.name) {
+                    vec![Action::Play {
+                        card: self.choose_card(),
+                    }]
+                } else {
+                    vec![]
+                }
+            }
+            Message::MissionResults { fails, passed, .. } => {
+                self.on_mission_results(*fails, *passed)
+            }
+            Message::ArthurDeclaration { player } => {
+                self.declared_arthur = Some(player.clone());
+                vec![]
+            }
+            Message::BeginAssassination { assassin, .. } if assassin == &self.name => {
+                self.assassinate()
+            }
+            // Actions sent while the game was paused are rejected, so try them again once it resumes. Any that were
+            // accepted before the pause are rejected the second time, which is harmless.
+            Message::Error(GameActionError::GamePaused) => {
+                self.pending = self.last_actions.clone();
+                vec![]
+            }
+            Message::GameResumed => std::mem::take(&mut self.pending),
+            Message::Error(error) => {
+                log::debug!("Bot {} made a mistake: {}", self.name, error);
+                vec![]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn role(&self) -> Option<Role> {
+        self.role.as_ref().map(|details| details.get_role())
+    }
+
+    fn is_evil(&self) -> bool {
+        matches!(&self.role, Some(details) if details.get_team() == Team::Evil)
+    }
+
+    /// Players this bot knows are on its team, including itself. Only Evil bots know their team.
+    fn teammates(&self) -> HashSet<&str> {
+        let mut teammates = HashSet::new();
+        teammates.insert(self.name.as_str());
+        if let Some(details) = &self.role {
+            teammates.extend(details.get_team_members().iter().map(String::as_str));
+        }
+        teammates
+    }
+
+    fn suspicion_of(&self, player: &str) -> i32 {
+        self.suspicion.get(player).cloned().unwrap_or(0)
+    }
+
+    fn on_role_information(&mut self, details: &RoleDetails) {
+        match details.get_role() {
+            Role::Merlin => {
+                for player in details.get_seen_players() {
+                    self.suspicion.insert(player.clone(), SEEN_EVIL_SUSPICION);
+                }
+            }
+            Role::Guinevere => {
+                for player in details.get_seen_players() {
+                    self.suspicion.insert(player.clone(), -SEEN_EVIL_SUSPICION);
+                }
+            }
+            _ => (),
+        }
+        self.role = Some(details.clone());
+    }
+
+    /// Picks players for a proposal. The bot always includes itself. Good bots fill the rest with the least
+    /// suspicious players, while Evil bots pick at random so they don't obviously avoid their teammates.
+    fn choose_proposal(&mut self) -> HashSet<String> {
+        let mut candidates = self
+            .players
+            .iter()
+            .filter(|player| *player != &self.name)
+            .filter(|player| self.mission == 5 || self.declared_arthur.as_ref() != Some(*player))
+            .cloned()
+            .collect::<Vec<_>>();
+        candidates.shuffle(&mut self.rng);
+        if !self.is_evil() {
+            // The sort is stable, so players with equal suspicion stay shuffled
+            candidates.sort_by_key(|player| self.suspicion_of(player));
+        }
+
+        let mut proposal = HashSet::with_capacity(self.mission_size);
+        if self.mission == 5 || self.declared_arthur.as_ref() != Some(&self.name) {
+            proposal.insert(self.name.clone());
+        }
+        for player in candidates {
+            if proposal.len() >= self.mission_size {
+                break;
+            }
+            proposal.insert(player);
+        }
+        proposal
+    }
+
+    /// Votes on the current proposal. On mission 1, upvoting sends the first proposal and downvoting sends the second.
+    fn vote(&mut self) -> Vec<Action> {
+        let upvote = match self.proposals.as_slice() {
+            [first, second] => self.score(first) >= self.score(second),
+            [.., proposal] => self.score(proposal) >= 0,
+            [] => true,
+        };
+
+        let mut actions = vec![Action::Vote { upvote }];
+        // Maeve obscures votes some of the time. The game tells us if we've run out of obscures.
+        if self.role() == Some(Role::Maeve) && self.rng.gen_bool(0.3) {
+            actions.push(Action::Obscure);
+        }
+        actions
+    }
+
+    /// How much this bot likes a proposal. Non-negative scores are worth voting for.
+    fn score(&self, proposal: &HashSet<String>) -> i32 {
+        if self.is_evil() {
+            // Evil wants at least one teammate on the mission to fail it
+            let teammates = self.teammates();
+            if proposal
+                .iter()
+                .any(|player| teammates.contains(player.as_str()))
+            {
+                1
+            } else {
+                -1
+            }
+        } else {
+            -proposal
+                .iter()
+                .map(|player| self.suspicion_of(player).max(0))
+                .sum::<i32>()
+        }
+    }
+
+    /// Chooses a card to play on a mission. Good bots always succeed, and Evil bots usually fail, except on mission 1
+    /// where failing is more likely to give them away.
+    fn choose_card(&mut self) -> Card {
+        let role = match self.role() {
+            Some(role) => role,
+            None => return Card::Success,
+        };
+
+        let fail_chance = if self.mission == 1 { 0.5 } else { 0.8 };
+        let card = if !self.is_evil() || !self.rng.gen_bool(fail_chance) {
+            Card::Success
+        } else if role.can_play(Card::Reverse) && self.rng.gen_bool(0.5) {
+            Card::Reverse
+        } else {
+            Card::Fail
+        };
+
+        if role.can_play(card) {
+            card
+        } else {
+            // Agravaine can only play fails
+            Card::Fail
+        }
+    }
+
+    fn on_mission_results(&mut self, fails: usize, passed: bool) -> Vec<Action> {
+        let change = if passed && fails == 0 {
+            -1
+        } else {
+            FAILED_MISSION_SUSPICION
+        };
+        for player in self.mission_players.iter() {
+            if player != &self.name {
+                *self.suspicion.entry(player.clone()).or_insert(0) += change;
+            }
+        }
+
+        // A mission that had fails but still passed is Agravaine's chance to declare
+        if self.role() == Some(Role::Agravaine)
+            && passed
+            && fails > 0
+            && self.mission_players.contains(&self.name)
+        {
+            vec![Action::Declare]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Picks assassination targets. Without knowing who the priority target is, the bot guesses Merlin.
+    fn assassinate(&mut self) -> Vec<Action> {
+        let target = self
+            .role
+            .as_ref()
+            .and_then(|details| details.get_priority_target())
+            .unwrap_or(PriorityTarget::Merlin);
+
+        let teammates = self.teammates();
+        let mut candidates = self
+            .players
+            .iter()
+            .filter(|player| !teammates.contains(player.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        candidates.shuffle(&mut self.rng);
+        // Good players who were on failed missions are less likely to know who's Evil
+        candidates.sort_by_key(|player| self.suspicion_of(player));
+
+        // The bot doesn't know which other roles are in the game, so it names the next most likely player as Percival,
+        // who is almost always present.
+        let mut candidates = candidates.into_iter();
+        let players = candidates
+            .by_ref()
+            .take(target.expected_targets())
+            .collect();
+        let other = candidates.next().map(|player| Accusation {
+            player,
+            role: Role::Percival,
+        });
+        vec![Action::Assassinate {
+            players,
+            target,
+            other,
+        }]
+    }
+}
+
+/// Plays a game as `bot`, sending its actions on `to_game` in response to messages on `from_game`. This returns when
+/// the game closes the channel to the bot.
+pub async fn run_bot(
+    mut bot: Bot,
+    mut to_game: mpsc::Sender<Action>,
+    mut from_game: mpsc::Receiver<Message>,
+) {
+    let pending = std::mem::take(&mut bot.pending);
+    for action in pending {
+        if to_game.send(action).await.is_err() {
+            return;
+        }
+    }
+
+    while let Some(message) = from_game.recv().await {
+        for action in bot.on_message(&message) {
+            log::debug!("Bot {} is performing {:?}", bot.name, action);
+            if to_game.send(action).await.is_err() {
+                log::warn!("Bot {} could not reach the game", bot.name);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::engine;
+    use super::super::history::{GameEvent, GameHistory, GameSetup};
+    use super::super::interactions::ChannelInteractions;
+    use super::super::{Game, Ruleset};
+    use super::*;
+
+    /// Plays a full game with only bots, making sure they can finish it.
+    async fn play_bot_game(players: usize, seed: u32, ruleset: Ruleset) -> GameHistory {
+        let names = (1..=players)
+            .map(|n| format!("Bot {}", n))
+            .collect::<Vec<_>>();
+        let game = Game::roll_with_seed(names.clone(), &ruleset, seed).unwrap();
+        let mut history = GameHistory::new(GameSetup::new(names.clone(), ruleset, &game));
+
+        let mut interactions = ChannelInteractions::new();
+        for (index, name) in names.into_iter().enumerate() {
+            let (action_tx, action_rx) = mpsc::channel(10);
+            let (message_tx, message_rx) = mpsc::channel(10);
+            interactions.add_player(name.clone(), action_rx, message_tx);
+            let bot = Bot::new(name, seed + index as u32);
+            tokio::spawn(run_bot(bot, action_tx, message_rx));
+        }
+
+        let (_controls, mut controls_rx) = mpsc::channel(1);
+        engine::run_game(game, &mut interactions, &mut history, &mut controls_rx)
+            .await
+            .unwrap();
+        history
+    }
+
+    #[tokio::test]
+    async fn test_bots_finish_games() {
+        for players in 5..=10 {
+            for seed in 0..3 {
+                let history = play_bot_game(players, seed, Ruleset::default()).await;
+                assert!(history.reveal.is_some());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agravaine_bot_declares() {
+        // Mission 4 needs two Fails in 8-player games, so Agravaine's Fail alone lets it pass and Agravaine declares
+        let ruleset = Ruleset {
+            evil_roles: Some(vec![Role::Agravaine, Role::Mordred, Role::Morgana]),
+            ..Ruleset::default()
+        };
+        let mut declared = false;
+        for seed in 0..20 {
+            let history = play_bot_game(8, seed, ruleset.clone()).await;
+            assert!(history.reveal.is_some());
+            declared |= history.events.iter().any(|event| {
+                matches!(
+                    event,
+                    GameEvent::Action {
+                        action: Action::Declare,
+                        ..
+                    }
+                )
+            });
+        }
+        assert!(declared, "No Agravaine bot declared");
+    }
+
+    #[test]
+    fn test_bot_retries_after_pause() {
+        let names = vec!["Alice", "Bob", "Charlie", "Dana", "Eve"]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let mut bot = Bot::new("Alice".to_string(), 0);
+        bot.on_message(&Message::ProposalOrder(names));
+        let proposal = bot.on_message(&Message::NextProposal {
+            proposer: "Alice".to_string(),
+            mission: 1,
+            mission_size: 2,
+            proposals_made: 0,
+            max_proposals: 5,
+        });
+        assert!(matches!(proposal.as_slice(), [Action::Propose { .. }]));
+
+        assert!(bot
+            .on_message(&Message::Error(GameActionError::GamePaused))
+            .is_empty());
+        assert_eq!(bot.on_message(&Message::GameResumed), proposal);
+        // Resuming again without another rejection doesn't repeat anything
+        assert!(bot.on_message(&Message::GameResumed).is_empty());
+    }
+
+    #[test]
+    fn test_bot_plays_legal_cards() {
+        let names = vec!["Alice", "Bob", "Charlie", "Dana", "Eve", "Frank", "Grace"]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let game = Game::roll_with_seed(names.clone(), &Ruleset::default(), 3).unwrap();
+        for name in names.iter() {
+            let details = game.info[name].clone();
+            let role = details.get_role();
+            let mut bot = Bot::new(name.clone(), 0);
+            bot.on_message(&Message::ProposalOrder(names.clone()));
+            bot.on_message(&Message::RoleInformation { details });
+
+            for mission in 1..=5 {
+                let going = bot.on_message(&Message::MissionGoing {
+                    mission,
+                    players: names.iter().cloned().collect(),
+                });
+                match going.as_slice() {
+                    [Action::Play { card }] => assert!(role.can_play(*card)),
+                    actions => panic!("Expected a card, got {:?}", actions),
+                }
+            }
+        }
+    }
+}
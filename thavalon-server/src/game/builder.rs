@@ -5,14 +5,25 @@ use crate::lobby::{LobbyChannel, LobbyCommand};
 use tokio::sync::mpsc;
 use tokio::task;
 
-use super::engine;
-use super::interactions::ChannelInteractions;
-use super::messages::{Action, Message};
+use super::bot::{self, Bot};
+use super::engine::{self, GameControl};
+use super::history::{GameHistory, GameSetup, Recorder};
+use super::interactions::{ChannelInteractions, Seats};
+use super::messages::{Action, GameError, Message};
 use super::snapshot::{SnapshotInteractions, Snapshots};
-use super::{CreateGameError, Game};
+use super::{CreateGameError, Game, Ruleset};
 
 use futures::future::{AbortRegistration, Abortable};
 
+/// Handle to the task running a game, which completes once the game has ended or been aborted.
+pub type GameTask = task::JoinHandle<std::result::Result<(), futures::future::Aborted>>;
+
+/// Handle for managing a game while it runs, such as pausing it or handing a player's seat to someone else.
+pub struct GameHandle {
+    controls: mpsc::Sender<GameControl>,
+    seats: Seats,
+}
+
 /// Builder for starting a new THavalon game
 pub struct GameBuilder {
     interactions: ChannelInteractions,
@@ -34,6 +45,13 @@ impl GameBuilder {
         (action_tx, message_rx)
     }
 
+    /// Add a bot player to the game. The bot plays through the same channels as a human player, and stops once the game
+    /// ends or it's removed with [`GameBuilder::remove_player`].
+    pub fn add_bot(&mut self, bot: Bot) {
+        let (to_game, from_game) = self.add_player(bot.name().to_string());
+        task::spawn(bot::run_bot(bot, to_game, from_game));
+    }
+
     pub fn remove_player(&mut self, name: &str) {
         self.interactions.remove_player(name);
         self.players.retain(|player| player != name);
@@ -43,34 +61,78 @@ impl GameBuilder {
         &self.players
     }
 
-    /// Start the game. This consumes `self` because no new players can be added once the game starts.
-    /// The returned [`task::JoinHandle`] will complete once the game has ended. The [`Snapshots`] may be
-    /// used to track per-player snapshots of the game state.
-    pub fn start(
+    /// Start the game using the house rules in `ruleset`. This consumes `self` because no new players can be added
+    /// once the game starts. The returned [`task::JoinHandle`] will complete once the game has ended. The [`Snapshots`]
+    /// may be used to track per-player snapshots of the game state, and the [`GameHandle`] manages the game while it
+    /// runs. The game's setup and every event in it are sent to `recorder`.
+    pub fn start<R: Recorder + Send + 'static>(
         self,
+        ruleset: &Ruleset,
+        mut recorder: R,
         mut lobby_channel: LobbyChannel,
         abort_registration: AbortRegistration,
-    ) -> Result<
-        (
-            Snapshots,
-            task::JoinHandle<std::result::Result<(), futures::future::Aborted>>,
-        ),
-        CreateGameError,
-    > {
+    ) -> Result<(Snapshots, GameHandle, GameTask), CreateGameError> {
+        let seats = self.interactions.seats();
         let mut interactions =
             SnapshotInteractions::new(self.interactions, self.players.iter().cloned());
-        let game = Game::roll(self.players)?;
+        let game = Game::roll(self.players.clone(), ruleset)?;
+        let setup = GameSetup::new(self.players, ruleset.clone(), &game);
         let snapshots = interactions.snapshots();
+        let (controls_tx, mut controls_rx) = mpsc::channel(10);
         let task_handle = task::spawn(Abortable::new(
             async move {
-                if let Err(e) = engine::run_game(game, &mut interactions).await {
+                recorder.start(&setup).await;
+                if let Err(e) =
+                    engine::run_game(game, &mut interactions, &mut recorder, &mut controls_rx).await
+                {
                     log::error!("Fatal game error: {}", e);
                 }
                 lobby_channel.send((LobbyCommand::EndGame, None)).await;
             },
             abort_registration,
         ));
-        Ok((snapshots, task_handle))
+        let handle = GameHandle {
+            controls: controls_tx,
+            seats,
+        };
+        Ok((snapshots, handle, task_handle))
+    }
+
+    /// Resume a game from its recorded `history`, for example after a server restart. Every player in the history
+    /// must already have been added with the same name. Snapshots are rebuilt from the history before the game
+    /// continues, and new events are sent to `recorder`. Otherwise, this works like [`GameBuilder::start`].
+    pub fn resume<R: Recorder + Send + 'static>(
+        self,
+        history: GameHistory,
+        mut recorder: R,
+        mut lobby_channel: LobbyChannel,
+        abort_registration: AbortRegistration,
+    ) -> Result<(Snapshots, GameHandle, GameTask), CreateGameError> {
+        // Make sure the game can be rebuilt before handing it off to the engine
+        history.setup.roll()?;
+
+        let seats = self.interactions.seats();
+        let mut interactions =
+            SnapshotInteractions::new(self.interactions, self.players.iter().cloned());
+        let snapshots = interactions.snapshots();
+        let (controls_tx, mut controls_rx) = mpsc::channel(10);
+        let task_handle = task::spawn(Abortable::new(
+            async move {
+                if let Err(e) =
+                    engine::resume_game(history, &mut interactions, &mut recorder, &mut controls_rx)
+                        .await
+                {
+                    log::error!("Fatal game error: {}", e);
+                }
+                let _ = lobby_channel.send((LobbyCommand::EndGame, None)).await;
+            },
+            abort_registration,
+        ));
+        let handle = GameHandle {
+            controls: controls_tx,
+            seats,
+        };
+        Ok((snapshots, handle, task_handle))
     }
 
     pub fn new() -> Self {
@@ -80,3 +142,23 @@ impl GameBuilder {
         }
     }
 }
+
+impl GameHandle {
+    /// Pause or resume the game.
+    pub async fn control(&mut self, control: GameControl) -> Result<(), GameError> {
+        self.controls
+            .send(control)
+            .await
+            .map_err(|_| GameError::NotRunning)
+    }
+
+    /// Hand `player`'s seat to someone else. The seat keeps its role, information, and anything it still has to do.
+    /// The player's old channels are closed, and the returned channels replace them like in
+    /// [`GameBuilder::add_player`].
+    pub async fn replace_player(
+        &mut self,
+        player: String,
+    ) -> Result<(mpsc::Sender<Action>, mpsc::Receiver<Message>), GameError> {
+        self.seats.replace(player).await
+    }
+}
@@ -1,17 +1,37 @@
 //! THavalon game engine, implemented as an async task. This starts a `GameState` state machine and runs it to completion.
 
+use std::time::Duration;
+
 use futures::future::{self, FutureExt};
-use tokio::time;
+use tokio::sync::mpsc;
+use tokio::time::{self, Instant};
 
+use super::history::{GameEvent, GameHistory, Recorder};
 use super::interactions::Interactions;
-use super::messages::GameError;
+use super::messages::{GameActionError, GameError, Message};
 use super::Game;
 
 use super::state::{Effect, GameStateWrapper};
 
-/// Runs a THavalon game to completion.
-pub async fn run_game<I: Interactions>(game: Game, interactions: &mut I) -> Result<(), GameError> {
-    let (mut state, initial_effects) = GameStateWrapper::new(game);
+/// A command that controls a running game from outside of it, rather than being a move in the game.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GameControl {
+    /// Freeze the game. Player actions are rejected and any active timeout is suspended until the game is resumed.
+    Pause,
+    /// Unfreeze a paused game, restarting any suspended timeout with the time it had left.
+    Resume,
+}
+
+/// Runs a THavalon game to completion. Every action, timeout, and pause is passed to `recorder` before it is applied, so
+/// the game can be replayed later. Games are paused and resumed through `controls`.
+pub async fn run_game<I: Interactions, R: Recorder>(
+    game: Game,
+    interactions: &mut I,
+    recorder: &mut R,
+    controls: &mut mpsc::Receiver<GameControl>,
+) -> Result<(), GameError> {
+    let (state, initial_effects) = GameStateWrapper::new(game);
+    let mut timeout = None;
     for effect in initial_effects {
         match effect {
             Effect::Broadcast(message) => {
@@ -24,25 +44,140 @@ pub async fn run_game<I: Interactions>(game: Game, interactions: &mut I) -> Resu
                     log::error!("Error sending message to {}: {}", player, e);
                 }
             }
+            // The first proposal may have a turn timer
+            Effect::StartTimeout(duration) => timeout = Some(duration),
             _ => panic!("Unexpected initial effect {:?}", effect),
         }
     }
 
+    play(state, timeout, false, interactions, recorder, controls).await
+}
+
+/// Resumes a game from its recorded `history`, for example after a server restart. Messages from the replayed part of
+/// the game are passed to [`Interactions::restore`] instead of being sent again, since players already received them.
+/// New events are passed to `recorder`, which should already contain `history`. A game that was paused stays paused.
+pub async fn resume_game<I: Interactions + Send, R: Recorder>(
+    history: GameHistory,
+    interactions: &mut I,
+    recorder: &mut R,
+    controls: &mut mpsc::Receiver<GameControl>,
+) -> Result<(), GameError> {
+    let game = history
+        .setup
+        .roll()
+        .map_err(|e| GameError::Internal(Box::new(e)))?;
+    let (mut state, effects) = GameStateWrapper::new(game);
+
+    // Track the timeout through the replay. If one is still active at the end, it's restarted in full since we can't
+    // know how much of it had elapsed before the restart.
+    let mut timeout = None;
+    let mut paused = false;
+    restore_effects(interactions, None, effects, &mut timeout).await?;
+    for event in history.events {
+        match event {
+            GameEvent::Pause { .. } => paused = true,
+            GameEvent::Resume { .. } => paused = false,
+            _ => (),
+        }
+        let player = event.player().map(|player| player.to_string());
+        let (next_state, effects) = event.apply(state);
+        restore_effects(interactions, player.as_deref(), effects, &mut timeout).await?;
+        state = next_state;
+    }
+
+    log::info!("Resumed game after replaying its history");
+    play(state, timeout, paused, interactions, recorder, controls).await
+}
+
+/// Passes the messages in `effects` to [`Interactions::restore`] and keeps track of the timeout they leave active.
+/// `player` is the player whose action caused the effects, if any.
+async fn restore_effects<I: Interactions + Send>(
+    interactions: &mut I,
+    player: Option<&str>,
+    effects: Vec<Effect>,
+    timeout: &mut Option<Duration>,
+) -> Result<(), GameError> {
+    for effect in effects {
+        match effect {
+            Effect::Broadcast(message) => interactions.restore(None, message).await?,
+            Effect::Send(player, message) => interactions.restore(Some(&player), message).await?,
+            Effect::Reply(message) => interactions.restore(player, message).await?,
+            Effect::StartTimeout(duration) => *timeout = Some(duration),
+            Effect::ClearTimeout => *timeout = None,
+        }
+    }
+    Ok(())
+}
+
+/// Runs the main game loop from `state` until the game is over. If `timeout` is set, that timeout is started first,
+/// unless the game starts `paused`, in which case it's held until the game is resumed.
+async fn play<I: Interactions, R: Recorder>(
+    mut state: GameStateWrapper,
+    timeout: Option<Duration>,
+    mut paused: bool,
+    interactions: &mut I,
+    recorder: &mut R,
+    controls: &mut mpsc::Receiver<GameControl>,
+) -> Result<(), GameError> {
     // At some points in the game, players have a certain time window to do something in. Using an
     // Either<Pending, Delay> means we can always use select below, without having to worry about whether or not there's
-    // an active timeout.
-    let mut timeout = future::pending().left_future();
+    // an active timeout. The deadline is tracked separately so a paused game knows how much time was left.
+    let mut deadline = None;
+    let mut timeout_future = future::pending().left_future();
+    // Time left on the timeout that was active when the game was paused, if any
+    let mut suspended = None;
+    if paused {
+        suspended = timeout;
+    } else if let Some(expires) = timeout.and_then(|duration| Instant::now().checked_add(duration))
+    {
+        deadline = Some(expires);
+        timeout_future = time::delay_until(expires).right_future();
+    }
 
     while !state.is_done() {
-        let ((next_state, effects), player) = tokio::select! {
-            _ = &mut timeout => {
+        let ((next_state, mut effects), player) = tokio::select! {
+            _ = &mut timeout_future => {
                 // Once the timeout future completes, we should reset it to the pending future. Otherwise, we'd keep
                 // polling the time::delay_for future after it's completed, which isn't necessarily supported.
-                timeout = future::pending().left_future();
+                timeout_future = future::pending().left_future();
+                deadline = None;
+                recorder.record(&GameEvent::timeout()).await;
                 (state.handle_timeout(), None)
             },
+            Some(control) = controls.recv() => {
+                let event = match control {
+                    GameControl::Pause if !paused => {
+                        log::info!("Pausing game");
+                        paused = true;
+                        suspended = deadline
+                            .take()
+                            .map(|deadline: Instant| deadline.saturating_duration_since(Instant::now()));
+                        timeout_future = future::pending().left_future();
+                        GameEvent::pause()
+                    }
+                    GameControl::Resume if paused => {
+                        log::info!("Resuming game");
+                        paused = false;
+                        GameEvent::resume()
+                    }
+                    _ => {
+                        log::warn!("Ignoring {:?}, the game is already in that state", control);
+                        continue;
+                    }
+                };
+                recorder.record(&event).await;
+                (event.apply(state), None)
+            },
             msg = interactions.receive() => match msg {
-                Ok((player, action)) => (state.handle_action(&player, action), Some(player)),
+                // Actions made while paused never reach the game state, so they aren't recorded either.
+                Ok((player, _)) if paused => (
+                    (state, vec![Effect::Reply(Message::Error(GameActionError::GamePaused))]),
+                    Some(player),
+                ),
+                Ok((player, action)) => {
+                    recorder.record(&GameEvent::action(&player, action.clone())).await;
+                    (state.handle_action(&player, action), Some(player))
+                }
                 Err(e) => {
                     log::error!("Could not receive player input: {}", e);
                     continue;
@@ -50,6 +185,16 @@ pub async fn run_game<I: Interactions>(game: Game, interactions: &mut I) -> Resu
             }
         };
 
+        // A resumed game picks its timeout back up with however much time was left on it.
+        if !paused {
+            if let Some(remaining) = suspended.take() {
+                effects.push(Effect::StartTimeout(remaining));
+                effects.push(Effect::Broadcast(Message::TurnTimer {
+                    seconds: remaining.as_secs(),
+                }));
+            }
+        }
+
         for effect in effects {
             match effect {
                 Effect::Broadcast(message) => {
@@ -63,26 +208,118 @@ pub async fn run_game<I: Interactions>(game: Game, interactions: &mut I) -> Resu
                     }
                 }
                 Effect::Reply(message) => {
-                    // player is only None if the timeout fired, and handle_timeout() should never return an
-                    // Effect::Reply because there's no player to reply to.
+                    // player is only None if the timeout fired or the game was paused or resumed, and none of those
+                    // should ever return an Effect::Reply because there's no player to reply to.
                     let player = player
                         .as_ref()
-                        .expect("handle_timeout() returned an Effect::Reply");
+                        .expect("Got an Effect::Reply with no player to reply to");
                     if let Err(e) = interactions.send_to(player, message).await {
                         log::error!("Error sending message to {}: {}", player, e);
                     }
                 }
-                Effect::StartTimeout(duration) => {
-                    timeout = time::delay_for(duration).right_future();
-                }
-                Effect::ClearTimeout => timeout = future::pending().left_future(),
-                Effect::Send(receiving_player, message) => {
-                    interactions.send_to(&receiving_player, message).await;
+                Effect::StartTimeout(duration) => match Instant::now().checked_add(duration) {
+                    Some(expires) => {
+                        deadline = Some(expires);
+                        timeout_future = time::delay_until(expires).right_future();
+                    }
+                    None => {
+                        log::error!("Timeout of {:?} is too long, running without one", duration);
+                        deadline = None;
+                        timeout_future = future::pending().left_future();
+                    }
+                },
+                Effect::ClearTimeout => {
+                    deadline = None;
+                    timeout_future = future::pending().left_future();
                 }
             }
         }
         state = next_state;
     }
 
+    if let Some(reveal) = state.reveal() {
+        recorder.finish(reveal).await;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use super::super::history::GameSetup;
+    use super::super::interactions::ChannelInteractions;
+    use super::super::messages::Action;
+    use super::super::Ruleset;
+    use super::*;
+
+    /// Waits for the next message that matches `predicate`, skipping any others.
+    async fn expect_message<F: Fn(&Message) -> bool>(
+        messages: &mut mpsc::Receiver<Message>,
+        predicate: F,
+    ) -> Message {
+        loop {
+            let message = messages
+                .recv()
+                .await
+                .expect("Game stopped sending messages");
+            if predicate(&message) {
+                return message;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        let names = vec!["Alice", "Bob", "Charlie", "Dana", "Eve"]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let game = Game::roll_with_seed(names.clone(), &Ruleset::default(), 0).unwrap();
+        let proposer = game.proposal_order()[0].clone();
+        let mut history = GameHistory::new(GameSetup::new(names, Ruleset::default(), &game));
+
+        // Only the proposer acts, but every player needs channels. Few enough messages are sent that the other players'
+        // channels never fill up.
+        let mut interactions = ChannelInteractions::new();
+        let mut channels = HashMap::new();
+        for name in history.setup.players.iter() {
+            let (action_tx, action_rx) = mpsc::channel(10);
+            let (message_tx, message_rx) = mpsc::channel(10);
+            interactions.add_player(name.clone(), action_rx, message_tx);
+            channels.insert(name.clone(), (action_tx, message_rx));
+        }
+        let (mut to_game, mut messages) = channels.remove(&proposer).unwrap();
+
+        let (mut controls, mut controls_rx) = mpsc::channel(10);
+        tokio::spawn(async move {
+            run_game(game, &mut interactions, &mut history, &mut controls_rx).await
+        });
+
+        let proposal = Action::Propose {
+            players: vec!["Alice", "Bob"]
+                .into_iter()
+                .map(String::from)
+                .collect::<HashSet<_>>(),
+        };
+
+        controls.send(GameControl::Pause).await.unwrap();
+        expect_message(&mut messages, |message| *message == Message::GamePaused).await;
+        to_game.send(proposal.clone()).await.unwrap();
+        let error = expect_message(&mut messages, |message| {
+            matches!(message, Message::Error(_) | Message::ProposalMade { .. })
+        })
+        .await;
+        assert_eq!(error, Message::Error(GameActionError::GamePaused));
+
+        controls.send(GameControl::Resume).await.unwrap();
+        expect_message(&mut messages, |message| *message == Message::GameResumed).await;
+        to_game.send(proposal).await.unwrap();
+        let made = expect_message(&mut messages, |message| {
+            matches!(message, Message::Error(_) | Message::ProposalMade { .. })
+        })
+        .await;
+        assert!(matches!(made, Message::ProposalMade { proposer: p, .. } if p == proposer));
+    }
+}
@@ -0,0 +1,391 @@
+//! Event-sourced game history. A game is fully determined by how it was rolled and the ordered stream of player actions
+//! and timeouts applied to it, so recording those is enough to rebuild the game state at any point.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::messages::{Action, GameReveal, Message};
+use super::state::{ActionResult, Effect, GameStateWrapper};
+use super::{CreateGameError, Game, Role, Ruleset};
+
+/// Everything needed to roll a game again.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameSetup {
+    /// The players in the game
+    pub players: Vec<String>,
+    /// House rules the game was rolled with
+    pub ruleset: Ruleset,
+    /// The seed the game was rolled from
+    pub seed: u32,
+    /// The role each player was assigned. Rolling the game again must reproduce these.
+    pub roles: HashMap<String, Role>,
+}
+
+/// Something that changed the state of a running game.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "eventType", rename_all = "camelCase")]
+pub enum GameEvent {
+    /// A player performed an action. This includes actions the game rejected, so a replay sees exactly what the
+    /// engine saw.
+    Action {
+        player: String,
+        action: Action,
+        /// When the action was received, in milliseconds since the Unix epoch
+        timestamp: i64,
+    },
+    /// An active timeout, such as the window for Agravaine to declare, expired.
+    Timeout {
+        /// When the timeout expired, in milliseconds since the Unix epoch
+        timestamp: i64,
+    },
+    /// The game was paused. Until it's resumed, no other events happen.
+    Pause {
+        /// When the game was paused, in milliseconds since the Unix epoch
+        timestamp: i64,
+    },
+    /// The game was resumed after being paused.
+    Resume {
+        /// When the game was resumed, in milliseconds since the Unix epoch
+        timestamp: i64,
+    },
+}
+
+/// The full history of a game: its setup and every event applied to it, in order.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameHistory {
+    pub setup: GameSetup,
+    pub events: Vec<GameEvent>,
+    /// Everything that happened in the game, once it's over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reveal: Option<GameReveal>,
+}
+
+/// Receives the history of a running game as it happens, for example to persist it.
+#[async_trait]
+pub trait Recorder {
+    /// Record the setup of a game, before any events have happened.
+    async fn start(&mut self, setup: &GameSetup);
+
+    /// Record an event applied to the game.
+    async fn record(&mut self, event: &GameEvent);
+
+    /// Record the post-game reveal once the game is over.
+    async fn finish(&mut self, reveal: &GameReveal);
+}
+
+impl GameSetup {
+    /// The setup of a game that has already been rolled from `players` and `ruleset`.
+    pub fn new(players: Vec<String>, ruleset: Ruleset, game: &Game) -> GameSetup {
+        GameSetup {
+            players,
+            ruleset,
+            seed: game.seed(),
+            roles: game
+                .players
+                .iter()
+                .map(|player| (player.name.clone(), player.role))
+                .collect(),
+        }
+    }
+
+    /// Roll the game described by this setup. This fails if the game no longer rolls the same way, for example because
+    /// the rolling logic changed since the setup was recorded.
+    pub fn roll(&self) -> Result<Game, CreateGameError> {
+        let game = Game::roll_with_seed(self.players.clone(), &self.ruleset, self.seed)?;
+        let reproduced = game
+            .players
+            .iter()
+            .all(|player| self.roles.get(&player.name) == Some(&player.role));
+        if reproduced && game.players.len() == self.roles.len() {
+            Ok(game)
+        } else {
+            Err(CreateGameError::IrreproducibleRoll(self.seed))
+        }
+    }
+}
+
+impl GameEvent {
+    /// An event for `player` performing `action` now.
+    pub fn action(player: &str, action: Action) -> GameEvent {
+        GameEvent::Action {
+            player: player.to_string(),
+            action,
+            timestamp: Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// An event for a timeout expiring now.
+    pub fn timeout() -> GameEvent {
+        GameEvent::Timeout {
+            timestamp: Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// An event for the game being paused now.
+    pub fn pause() -> GameEvent {
+        GameEvent::Pause {
+            timestamp: Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// An event for the game being resumed now.
+    pub fn resume() -> GameEvent {
+        GameEvent::Resume {
+            timestamp: Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// The player who caused this event, if any.
+    pub fn player(&self) -> Option<&str> {
+        match self {
+            GameEvent::Action { player, .. } => Some(player),
+            GameEvent::Timeout { .. } | GameEvent::Pause { .. } | GameEvent::Resume { .. } => None,
+        }
+    }
+
+    /// Apply this event to `state`, returning the new state and its side-effects. Pausing and resuming don't change
+    /// the game state, but are still announced to players. Nobody can act while the game is paused, so their legal
+    /// actions are cleared until it resumes.
+    pub fn apply(self, state: GameStateWrapper) -> ActionResult {
+        match self {
+            GameEvent::Action { player, action, .. } => state.handle_action(&player, action),
+            GameEvent::Timeout { .. } => state.handle_timeout(),
+            GameEvent::Pause { .. } => {
+                let mut effects = vec![Effect::Broadcast(Message::GamePaused)];
+                state.clear_legal_actions(&mut effects);
+                (state, effects)
+            }
+            GameEvent::Resume { .. } => {
+                let mut effects = vec![Effect::Broadcast(Message::GameResumed)];
+                state.push_legal_actions(&HashMap::new(), &mut effects);
+                (state, effects)
+            }
+        }
+    }
+}
+
+impl GameHistory {
+    pub fn new(setup: GameSetup) -> GameHistory {
+        GameHistory {
+            setup,
+            events: Vec::new(),
+            reveal: None,
+        }
+    }
+
+    /// Rebuilds the game state after the first `events` events have been applied. Replaying 0 events gives the state
+    /// at the start of the game, and replaying `self.events.len()` events gives the latest state.
+    #[cfg(test)]
+    pub fn replay(&self, events: usize) -> Result<GameStateWrapper, CreateGameError> {
+        let game = self.setup.roll()?;
+        let (state, _) = GameStateWrapper::new(game);
+        Ok(self
+            .events
+            .iter()
+            .take(events)
+            .cloned()
+            .fold(state, |state, event| event.apply(state).0))
+    }
+
+    /// Replays the game, collecting every message `player` received, in order. This includes replies to their own
+    /// actions.
+    pub fn messages_for(&self, player: &str) -> Result<Vec<Message>, CreateGameError> {
+        let game = self.setup.roll()?;
+        let (mut state, effects) = GameStateWrapper::new(game);
+        let mut messages = Vec::new();
+        collect_messages(&mut messages, player, None, effects);
+        for event in self.events.iter().cloned() {
+            let actor = event.player().map(|actor| actor.to_string());
+            let (next_state, effects) = event.apply(state);
+            collect_messages(&mut messages, player, actor.as_deref(), effects);
+            state = next_state;
+        }
+        Ok(messages)
+    }
+
+    /// Rebuilds the latest game state.
+    #[cfg(test)]
+    pub fn replay_all(&self) -> Result<GameStateWrapper, CreateGameError> {
+        self.replay(self.events.len())
+    }
+}
+
+/// Adds the messages in `effects` that `player` receives to `messages`. `actor` is the player whose action caused the
+/// effects, if any.
+fn collect_messages(
+    messages: &mut Vec<Message>,
+    player: &str,
+    actor: Option<&str>,
+    effects: Vec<Effect>,
+) {
+    for effect in effects {
+        match effect {
+            Effect::Broadcast(message) => messages.push(message),
+            Effect::Send(recipient, message) if recipient == player => messages.push(message),
+            Effect::Reply(message) if actor == Some(player) => messages.push(message),
+            _ => (),
+        }
+    }
+}
+
+/// Keeping a history in memory is the simplest way to record a game.
+#[async_trait]
+impl Recorder for GameHistory {
+    async fn start(&mut self, setup: &GameSetup) {
+        self.setup = setup.clone();
+        self.events.clear();
+        self.reveal = None;
+    }
+
+    async fn record(&mut self, event: &GameEvent) {
+        self.events.push(event.clone());
+    }
+
+    async fn finish(&mut self, reveal: &GameReveal) {
+        self.reveal = Some(reveal.clone());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::super::state::GameStateWrapper;
+    use super::*;
+
+    fn history() -> GameHistory {
+        let players = vec!["Alice", "Bob", "Charlie", "Dana", "Eve"]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let game = Game::roll_with_seed(players.clone(), &Ruleset::default(), 7).unwrap();
+        let order = game.proposal_order().to_vec();
+        let mut history = GameHistory::new(GameSetup::new(players, Ruleset::default(), &game));
+
+        let proposal = order[..2].iter().cloned().collect::<HashSet<_>>();
+        for proposer in order[..2].iter() {
+            history.events.push(GameEvent::action(
+                proposer,
+                Action::Propose {
+                    players: proposal.clone(),
+                },
+            ));
+        }
+        for player in order.iter() {
+            history
+                .events
+                .push(GameEvent::action(player, Action::Vote { upvote: true }));
+        }
+        history
+    }
+
+    #[test]
+    fn test_replay() {
+        let history = history();
+
+        assert!(matches!(
+            history.replay(0).unwrap(),
+            GameStateWrapper::Proposing(_)
+        ));
+        assert!(matches!(
+            history.replay(1).unwrap(),
+            GameStateWrapper::Proposing(_)
+        ));
+        assert!(matches!(
+            history.replay(2).unwrap(),
+            GameStateWrapper::Voting(_)
+        ));
+        assert!(matches!(
+            history.replay_all().unwrap(),
+            GameStateWrapper::OnMission(_)
+        ));
+    }
+
+    #[test]
+    fn test_irreproducible_roll() {
+        let mut history = history();
+        assert!(history.setup.roll().is_ok());
+
+        let role = history.setup.roles.values_mut().next().unwrap();
+        *role = if *role == Role::Merlin {
+            Role::Mordred
+        } else {
+            Role::Merlin
+        };
+        assert!(matches!(
+            history.setup.roll(),
+            Err(CreateGameError::IrreproducibleRoll(7))
+        ));
+        assert!(history.replay_all().is_err());
+    }
+
+    #[test]
+    fn test_messages_for() {
+        let history = history();
+        let game = history.setup.roll().unwrap();
+        for player in history.setup.players.iter() {
+            let messages = history.messages_for(player).unwrap();
+            let role_information = messages
+                .iter()
+                .filter_map(|message| match message {
+                    Message::RoleInformation { details } => Some(details),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(role_information, vec![&game.info[player]]);
+            assert!(matches!(
+                messages
+                    .iter()
+                    .rev()
+                    .find(|message| !matches!(message, Message::LegalActions { .. })),
+                Some(Message::MissionGoing { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_pause_clears_legal_actions() {
+        let history = history();
+        let state = history.replay_all().unwrap();
+        let sent_to = |effects: &[Effect], player: &str| {
+            effects
+                .iter()
+                .find_map(|effect| match effect {
+                    Effect::Send(recipient, Message::LegalActions { actions })
+                        if recipient == player =>
+                    {
+                        Some(actions.clone())
+                    }
+                    _ => None,
+                })
+                .expect("No legal actions sent")
+        };
+
+        let (state, effects) = GameEvent::pause().apply(state);
+        for player in history.setup.players.iter() {
+            assert!(sent_to(&effects, player).is_empty());
+        }
+
+        let (state, effects) = GameEvent::resume().apply(state);
+        for player in history.setup.players.iter() {
+            assert_eq!(sent_to(&effects, player), state.legal_actions(player));
+        }
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let history = history();
+        let json = serde_json::to_string(&history).unwrap();
+        let parsed: GameHistory = serde_json::from_str(&json).unwrap();
+        assert_eq!(history, parsed);
+        assert!(matches!(
+            parsed.replay_all().unwrap(),
+            GameStateWrapper::OnMission(_)
+        ));
+    }
+}
@@ -19,19 +19,48 @@ pub trait Interactions {
 
     /// Receive the next message from any player
     async fn receive(&mut self) -> Result<(String, Action), GameError>;
+
+    /// Restore a message that was already sent before the game was restored from its history. `player` is the
+    /// recipient, or `None` for broadcasts. Implementations that track game state should update it, but the message
+    /// must not be delivered again.
+    async fn restore(&mut self, _player: Option<&str>, _message: Message) -> Result<(), GameError> {
+        Ok(())
+    }
 }
 
+/// New channels for a player's seat, sent through [`Seats`]
+type SeatChange = (String, mpsc::Receiver<Action>, mpsc::Sender<Message>);
+
 /// An Interactions that uses per-player MPSC channels
 pub struct ChannelInteractions {
     inbox: StreamMap<String, mpsc::Receiver<Action>>,
     outbox: HashMap<String, mpsc::Sender<Message>>,
+    seat_changes: mpsc::Receiver<SeatChange>,
+    seats: mpsc::Sender<SeatChange>,
+}
+
+/// Handle for giving players new channels while a game using [`ChannelInteractions`] is running. This is how a player's
+/// seat is handed to someone else.
+#[derive(Clone)]
+pub struct Seats {
+    changes: mpsc::Sender<SeatChange>,
 }
 
 impl ChannelInteractions {
     pub fn new() -> ChannelInteractions {
+        let (seats, seat_changes) = mpsc::channel(10);
         ChannelInteractions {
             inbox: StreamMap::new(),
             outbox: HashMap::new(),
+            seat_changes,
+            seats,
+        }
+    }
+
+    /// Create a new [`Seats`] handle for replacing players' channels once the game is running.
+    pub fn seats(&self) -> Seats {
+        Seats {
+            changes: self.seats.clone(),
         }
     }
 
@@ -72,21 +101,52 @@ impl Interactions for ChannelInteractions {
     }
 
     async fn receive(&mut self) -> Result<(String, Action), GameError> {
-        match self.inbox.next().await {
-            Some(msg) => Ok(msg),
-            None => Err(GameError::PlayerDisconnected),
+        // Seat changes are applied while waiting for actions, which is where the game spends almost all of its time.
+        loop {
+            tokio::select! {
+                Some((name, incoming, outgoing)) = self.seat_changes.recv() => {
+                    log::info!("Replacing the channels for {}", name);
+                    self.add_player(name, incoming, outgoing);
+                }
+                msg = self.inbox.next() => {
+                    return match msg {
+                        Some(msg) => Ok(msg),
+                        None => Err(GameError::PlayerDisconnected),
+                    };
+                }
+            }
         }
     }
 }
 
+impl Seats {
+    /// Replace the channels for `player`'s seat. The old channels are closed, and from now on the player's actions
+    /// should be sent to the returned [`mpsc::Sender`] and messages on the returned [`mpsc::Receiver`] shown to them.
+    /// Only the channels change, so the seat keeps its role and everything else about it.
+    pub async fn replace(
+        &mut self,
+        player: String,
+    ) -> Result<(mpsc::Sender<Action>, mpsc::Receiver<Message>), GameError> {
+        let (action_tx, action_rx) = mpsc::channel(10);
+        let (message_tx, message_rx) = mpsc::channel(10);
+        self.changes
+            .send((player, action_rx, message_tx))
+            .await
+            .map_err(|_| GameError::NotRunning)?;
+        Ok((action_tx, message_rx))
+    }
+}
+
 #[cfg(test)]
 pub(super) mod test {
     use std::collections::VecDeque;
 
     use async_trait::async_trait;
 
+    use tokio::sync::mpsc;
+
     use super::super::messages::{Action, GameError, Message};
-    use super::Interactions;
+    use super::{ChannelInteractions, Interactions};
 
     pub struct TestInteractions {
         broadcasts: Vec<Message>,
@@ -152,4 +212,31 @@ pub(super) mod test {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_replace_seat() {
+        let mut interactions = ChannelInteractions::new();
+        let (mut old_actions, action_rx) = mpsc::channel(10);
+        let (message_tx, mut old_messages) = mpsc::channel(10);
+        interactions.add_player("Alice".to_string(), action_rx, message_tx);
+
+        let mut seats = interactions.seats();
+        let (mut actions, mut messages) = seats.replace("Alice".to_string()).await.unwrap();
+
+        // The new channels take over the next time the game waits for an action
+        actions.send(Action::Declare).await.unwrap();
+        assert_eq!(
+            interactions.receive().await.unwrap(),
+            ("Alice".to_string(), Action::Declare)
+        );
+        interactions
+            .send_to("Alice", Message::ArthurCanDeclare)
+            .await
+            .unwrap();
+        assert_eq!(messages.recv().await, Some(Message::ArthurCanDeclare));
+
+        // The old channels are closed
+        assert!(old_actions.send(Action::Declare).await.is_err());
+        assert_eq!(old_messages.recv().await, None);
+    }
 }
@@ -5,13 +5,13 @@ use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use super::role::{PriorityTarget, RoleDetails, Team};
+use super::role::{PriorityTarget, Role, RoleDetails, Team};
 use super::{Card, MissionNumber};
 
 // Game-related messages
 
 /// Something the player tries to do
-#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Action {
     Propose {
         players: HashSet<String>,
@@ -34,8 +34,30 @@ pub enum Action {
     Assassinate {
         players: HashSet<String>,
         target: PriorityTarget,
+        /// The second target of a full assassination. This is left out when making a single pick after moving to
+        /// assassinate.
+        #[serde(default)]
+        other: Option<Accusation>,
     },
     MoveToAssassination,
+    /// Sends a message to the rest of the Evil team while discussing the assassination
+    Discuss {
+        message: String,
+    },
+    /// Suggests targets to the assassin while discussing the assassination
+    Nominate {
+        players: HashSet<String>,
+        target: PriorityTarget,
+        #[serde(default)]
+        other: Option<Accusation>,
+    },
+}
+
+/// Naming a player as a specific role during an assassination
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Accusation {
+    pub player: String,
+    pub role: Role,
 }
 
 /// A message from the game to a player
@@ -43,8 +65,8 @@ pub enum Action {
 #[serde(tag = "messageType", content = "data")]
 #[serde(rename_all = "camelCase")]
 pub enum Message {
-    /// Error message, usually when a player does something wrong
-    Error(String),
+    /// Error message, when a player does something the rules don't allow
+    Error(GameActionError),
 
     /// The proposal order of the players in the game. Sent at the start of the game.
     ProposalOrder(Vec<String>),
@@ -82,11 +104,15 @@ pub enum Message {
     /// Announces that players should submit votes for the latest proposal.
     CommenceVoting,
 
-    /// Announces that a player has submitted a valid vote.
-    VoteReceived,
+    /// Announces that a player has submitted a valid vote, without revealing how they voted.
+    VoteReceived { player: String },
 
-    /// Announces the results of a vote
-    VotingResults { sent: bool, counts: VoteCounts },
+    /// Announces the results of a vote, along with how many votes each player cast. Obscured counts are weighted.
+    VotingResults {
+        sent: bool,
+        counts: VoteCounts,
+        weights: HashMap<String, u32>,
+    },
 
     /// Announces that a mission is going
     MissionGoing {
@@ -94,6 +120,9 @@ pub enum Message {
         players: HashSet<String>,
     },
 
+    /// Announces that a player on the mission has played a card, without revealing which.
+    CardPlayed { player: String },
+
     /// Announces the results of a mission going
     MissionResults {
         mission: MissionNumber,
@@ -104,6 +133,9 @@ pub enum Message {
         passed: bool,
     },
 
+    /// Sent to Tristan and Iseult once they've been on a mission together, revealing their Lover to them.
+    LoversRevealed { lover: String },
+
     /// Agravaine declared, so the given mission now failed.
     AgravaineDeclaration {
         mission: MissionNumber,
@@ -111,7 +143,11 @@ pub enum Message {
     },
 
     /// Assassination has begun. This can either be because 3 missions passed or because the assassin moved to assassinate.
-    BeginAssassination { assassin: String },
+    BeginAssassination {
+        assassin: String,
+        /// Whether the assassin moved to assassinate, which lets them make a single pick instead of naming two targets
+        moved_early: bool,
+    },
 
     /// The results of an assassination attempt.
     AssassinationResult {
@@ -119,16 +155,33 @@ pub enum Message {
         players: HashSet<String>,
         /// What the players were assassinated as
         target: PriorityTarget,
+        /// The other player named in a full assassination
+        other: Option<Accusation>,
         /// Whether or not the assassination was correct
         correct: bool,
     },
 
+    /// Sent to Evil players when a teammate says something while discussing the assassination
+    EvilDiscussion { player: String, message: String },
+
+    /// Sent to Evil players when a teammate suggests assassination targets
+    Nomination(Nomination),
+
+    /// Sent to Evil players when discussion time is up, leaving the decision to the assassin
+    DiscussionOver,
+
     /// Sent to Arthur to indicate that they can declare
     ArthurCanDeclare,
 
     /// Sent to Arthur to indicate that they cannot declare
     ArthurCannotDeclare,
 
+    /// Sent to Maeve at the start of each round and whenever she obscures, so she knows if she can obscure votes.
+    ObscureStatus {
+        can_obscure: bool,
+        obscures_remaining: usize,
+    },
+
     /// Announces that Arthur has declared
     // TODO: generic Declaration message with the player and role instead?
     ArthurDeclaration { player: String },
@@ -137,8 +190,30 @@ pub enum Message {
     GameOver {
         winning_team: Team,
         roles: HashMap<String, RoleDetails>,
+        /// The seed the game was rolled from, so it can be re-rolled later
+        seed: u32,
     },
 
+    /// Sent after [`Message::GameOver`] to reveal everything that happened in the game.
+    GameReveal(GameReveal),
+
+    /// Announces that the current phase has a time limit, so clients can show a countdown. Once it runs out, the game
+    /// acts for any players who haven't.
+    TurnTimer {
+        /// Seconds until the timer runs out
+        seconds: u64,
+    },
+
+    /// Announces that the game was paused. Actions are rejected until it's resumed.
+    GamePaused,
+
+    /// Announces that a paused game was resumed.
+    GameResumed,
+
+    /// Sent to a player whenever what they're allowed to do changes, listing everything they can do right now. While
+    /// the game is paused, this is empty.
+    LegalActions { actions: Vec<LegalAction> },
+
     /// Message that a client should surface to the end user.
     Toast {
         severity: ToastSeverity,
@@ -146,6 +221,44 @@ pub enum Message {
     },
 }
 
+/// Something a player is allowed to do. These mirror [`Action`]s, but describe the choices available instead of the
+/// choice made.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(tag = "actionType")]
+pub enum LegalAction {
+    /// Propose `size` players, chosen from `players`. This also covers selecting and unselecting players.
+    Propose {
+        size: usize,
+        players: HashSet<String>,
+    },
+    Vote,
+    Obscure,
+    /// Play one of `cards` on the mission
+    Play {
+        cards: Vec<Card>,
+    },
+    QuestingBeast,
+    Declare,
+    MoveToAssassination,
+    Assassinate(AssassinationOptions),
+    Discuss,
+    Nominate(AssassinationOptions),
+}
+
+/// Who can be named during an assassination, and as what
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssassinationOptions {
+    /// Players who can be named
+    pub players: HashSet<String>,
+    /// Priority targets that can be named
+    pub targets: Vec<PriorityTarget>,
+    /// Roles the other target can be named as
+    pub roles: Vec<Role>,
+    /// Whether naming a single player as a priority target is allowed, instead of naming two targets
+    pub single_pick: bool,
+}
+
 /// Severity of a toast notification
 #[derive(Debug, Clone, Eq, PartialEq, Serialize)]
 pub enum ToastSeverity {
@@ -154,6 +267,16 @@ pub enum ToastSeverity {
     URGENT,
 }
 
+/// Assassination targets suggested by an Evil player
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Nomination {
+    /// The player making the suggestion
+    pub player: String,
+    pub players: HashSet<String>,
+    pub target: PriorityTarget,
+    pub other: Option<Accusation>,
+}
+
 /// How players voted on a proposal
 #[derive(Debug, Clone, Eq, PartialEq, Serialize)]
 #[serde(tag = "voteType")]
@@ -167,11 +290,177 @@ pub enum VoteCounts {
     Obscured { upvotes: u32, downvotes: u32 },
 }
 
+/// The full history of a finished game, including everything that was hidden while it was played.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameReveal {
+    pub winning_team: Team,
+    /// Each player's role
+    pub roles: HashMap<String, Role>,
+    pub assassin: String,
+    pub priority_target: PriorityTarget,
+    /// Every proposal made, in order
+    pub proposals: Vec<ProposalRecord>,
+    /// Every mission that went, in order
+    pub missions: Vec<MissionRecord>,
+    /// The assassination attempt, if the game got that far
+    pub assassination: Option<AssassinationRecord>,
+}
+
+/// A proposal made during a game
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalRecord {
+    pub mission: MissionNumber,
+    pub proposer: String,
+    pub players: HashSet<String>,
+    /// How each player really voted, or `None` if the proposal wasn't voted on. On mission 1, both proposals are
+    /// decided by a single vote, which is recorded on the second proposal. An upvote sends the first proposal.
+    pub votes: Option<HashMap<String, bool>>,
+    /// Whether Maeve obscured the votes on this proposal
+    pub obscured: bool,
+    /// Whether this proposal went on a mission
+    pub sent: bool,
+}
+
+/// A mission that went during a game
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissionRecord {
+    pub mission: MissionNumber,
+    /// Index of the proposal that went, in [`GameReveal::proposals`]
+    pub proposal: usize,
+    /// The card each player on the mission played
+    pub cards: HashMap<String, Card>,
+    pub questing_beasts: usize,
+    pub passed: bool,
+    /// Whether Agravaine had a chance to declare and fail the mission
+    pub agravaine_window: bool,
+    /// The player who declared as Agravaine to fail the mission, if anyone did
+    pub agravaine_declaration: Option<String>,
+}
+
+/// The assassin's choice at the end of a game
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssassinationRecord {
+    pub assassin: String,
+    pub players: HashSet<String>,
+    pub target: PriorityTarget,
+    pub other: Option<Accusation>,
+    pub correct: bool,
+}
+
+/// Why the game rejected a player's action. Each variant serializes with a stable `code`, so clients can react to
+/// specific errors and show their own text for them.
+#[derive(Error, Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(tag = "code", rename_all = "camelCase")]
+pub enum GameActionError {
+    #[error("The game is paused")]
+    GamePaused,
+
+    #[error("You can't do that right now")]
+    WrongPhase,
+
+    #[error("{player} is not in the game")]
+    NotInGame { player: String },
+
+    #[error("It's not your proposal")]
+    NotProposer,
+
+    #[error("{player} can't go on missions until mission 5")]
+    IneligiblePlayer { player: String },
+
+    #[error("{player} wasn't on the proposal")]
+    NotOnProposal { player: String },
+
+    #[error("Proposal must contain {expected} players")]
+    ProposalSize { expected: usize, actual: usize },
+
+    #[error("You already voted")]
+    AlreadyVoted,
+
+    #[error("You can't obscure votes")]
+    CannotObscure,
+
+    #[error("You already obscured the votes for this proposal")]
+    AlreadyObscured,
+
+    #[error("You can't obscure this round")]
+    ObscureUnavailable,
+
+    #[error("You're not on the mission")]
+    NotOnMission,
+
+    #[error("You already played a {card}")]
+    AlreadyPlayed { card: Card },
+
+    #[error("You can't play a {card}")]
+    CannotPlay { card: Card },
+
+    #[error("You can't play the Questing Beast")]
+    CannotPlayQuestingBeast,
+
+    #[error("You must play the Questing Beast before your mission card")]
+    QuestingBeastAfterCard,
+
+    #[error("You already played the Questing Beast on this mission")]
+    QuestingBeastAlreadyPlayed,
+
+    #[error("You have no Questing Beasts left to play")]
+    NoQuestingBeastsLeft,
+
+    #[error("You can't declare right now")]
+    CannotDeclare,
+
+    #[error("You are not the assassin")]
+    NotAssassin,
+
+    #[error("You can't move to assassination right now")]
+    CannotMoveToAssassination,
+
+    #[error("You must assassinate {expected} players as {target:?}")]
+    AssassinationSize {
+        target: PriorityTarget,
+        expected: usize,
+    },
+
+    #[error("You already named {player} as {target:?}")]
+    AlreadyNamed {
+        player: String,
+        target: PriorityTarget,
+    },
+
+    #[error("You can't assassinate anyone as {role:?}")]
+    CannotAccuse { role: Role },
+
+    #[error("You must name one other target along with the priority target")]
+    MissingOtherTarget,
+
+    #[error("You must either name one player as a priority target, or name two targets")]
+    InvalidSinglePick,
+
+    #[error("You can't make a single pick after Evil has discussed")]
+    SinglePickAfterDiscussion,
+
+    #[error("Only Evil players can discuss the assassination")]
+    NotEvil,
+
+    #[error("Colgrevance can't join the discussion without revealing themselves")]
+    ColgrevanceHidden,
+
+    #[error("The discussion is over")]
+    DiscussionOver,
+}
+
 #[derive(Error, Debug, Serialize)]
 pub enum GameError {
     #[error("Could not communicate with player")]
     PlayerDisconnected,
 
+    #[error("The game is no longer running")]
+    NotRunning,
+
     #[error("Internal interaction error")]
     #[serde(serialize_with = "serialize_internal_error")]
     Internal(#[from] Box<dyn std::error::Error + Send + 'static>),
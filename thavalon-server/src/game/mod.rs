@@ -8,38 +8,52 @@
 //! - [`GameState`] and [`Phase`] implement a state machine for while the game is running.
 //! - [`Interactions`] abstracts over communication with the players.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
+use std::time::Duration;
 
 use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod bot;
 pub mod builder;
 mod engine;
+pub mod history;
 mod interactions;
 pub mod messages;
 mod role;
+mod ruleset;
+pub mod simulation;
 pub mod snapshot;
 mod state;
 
+pub use self::engine::GameControl;
 pub use self::messages::{Action, Message};
 pub use self::role::*;
+pub use self::ruleset::Ruleset;
 
 /// A mission number (from 1 to 5)
 pub type MissionNumber = u8;
 
+/// The random number generator used to roll games. This is a fixed algorithm, rather than [`StdRng`], so that seeds
+/// keep producing the same games. Seeds are 32 bits so they fit in both BSON and JavaScript numbers.
+type GameRng = ChaCha20Rng;
+
 /// Game rules determined by the number of players
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GameSpec {
     /// Number of players in the game
     pub players: u8,
     /// The number of players on each mission
     pub mission_sizes: [usize; 5],
     /// Allowed good roles in the game
-    pub good_roles: &'static [Role],
+    pub good_roles: Cow<'static, [Role]>,
     /// Allowed evil roles in the game
-    pub evil_roles: &'static [Role],
+    pub evil_roles: Cow<'static, [Role]>,
     /// The number of players on the good team
     pub good_players: u8,
     /// The maximum number of proposals allowed before force activates. Proposals on mission 1 and proposals that are
@@ -49,6 +63,23 @@ pub struct GameSpec {
     pub max_maeve_obscures: usize,
     /// True if mission 4 requires at least two failures
     double_fail_mission_four: bool,
+    /// Time limits for each player's turn
+    pub turn_timers: TurnTimers,
+}
+
+/// Time limits for the phases where the game waits on players. If a phase has no time limit, the game waits
+/// indefinitely. Otherwise, the game picks a default action for any players who run out of time.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnTimers {
+    /// How long the proposer has to make a proposal before it passes to the next player
+    pub proposal: Option<Duration>,
+    /// How long players have to vote before missing votes count as downvotes
+    pub voting: Option<Duration>,
+    /// How long players on a mission have to play a card before one is played for them
+    pub mission: Option<Duration>,
+    /// How long Evil has to discuss the assassination before the assassin decides alone
+    pub discussion: Option<Duration>,
 }
 /// Fixed information about a player, decided at startup
 #[derive(Debug, Clone)]
@@ -80,19 +111,51 @@ pub struct Game {
     proposal_order: Vec<String>,
     assassin: String,
     priority_target: PriorityTarget,
-    spec: &'static GameSpec,
+    spec: GameSpec,
+    seed: u32,
 }
 
 #[derive(Debug, Clone, Error)]
 pub enum CreateGameError {
     #[error("{0}-player games not supported")]
     UnsupportedSize(usize),
+    #[error("Mission {mission} cannot have {size} players")]
+    InvalidMissionSize { mission: MissionNumber, size: usize },
+    #[error("{needed} {team:?} roles are needed, but only {available} are allowed")]
+    NotEnoughRoles {
+        team: Team,
+        needed: usize,
+        available: usize,
+    },
+    #[error("{0} is not on the right team")]
+    WrongTeam(Role),
+    #[error("{0} is allowed more than once")]
+    DuplicateRole(Role),
+    #[error("At least one proposal must be allowed before force")]
+    InvalidMaxProposals,
+    #[error("Mission 4 cannot require two failures with only {0} players on it")]
+    InvalidDoubleFail(usize),
+    #[error("Turn timers must be between 1 second and 1 hour")]
+    InvalidTimer,
+    #[error("Seed {0} no longer rolls the recorded game")]
+    IrreproducibleRoll(u32),
 }
 
 impl Game {
-    pub fn roll(mut names: Vec<String>) -> Result<Game, CreateGameError> {
-        let spec = GameSpec::for_players(names.len())?;
-        let mut rng = thread_rng();
+    /// Roll a new game for the players in `names`, using any house rules in `ruleset` and a random seed.
+    pub fn roll(names: Vec<String>, ruleset: &Ruleset) -> Result<Game, CreateGameError> {
+        Game::roll_with_seed(names, ruleset, thread_rng().gen())
+    }
+
+    /// Roll a new game from `seed`. Rolling the same players with the same rules and seed always produces the same
+    /// game, regardless of the order of `names`.
+    pub fn roll_with_seed(
+        mut names: Vec<String>,
+        ruleset: &Ruleset,
+        seed: u32,
+    ) -> Result<Game, CreateGameError> {
+        let spec = ruleset.to_spec(names.len())?;
+        let mut rng = GameRng::seed_from_u64(seed.into());
 
         let good_roles = spec
             .good_roles
@@ -101,15 +164,58 @@ impl Game {
             .evil_roles
             .choose_multiple(&mut rng, spec.evil_players());
 
+        // Sort first so the shuffle only depends on the seed and not on the order players joined in
+        names.sort();
         names.shuffle(&mut rng);
         let mut players = Players::new();
         for (role, name) in good_roles.chain(evil_roles).cloned().zip(names.into_iter()) {
             players.add_player(name, role);
         }
 
+        Ok(Game::assign(players, spec, seed, &mut rng))
+    }
+
+    /// Create a game where each player has a specific role, for tests that need a particular setup. The assassin,
+    /// priority target, and proposal order are still rolled from `seed`. The roles don't have to come from the
+    /// ruleset's role pools.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the roles don't split into the right number of Good and Evil players.
+    #[cfg(test)]
+    pub fn with_roles(
+        roles: Vec<(String, Role)>,
+        ruleset: &Ruleset,
+        seed: u32,
+    ) -> Result<Game, CreateGameError> {
+        let spec = ruleset.to_spec(roles.len())?;
+        let good = roles
+            .iter()
+            .filter(|(_, role)| role.team() == Team::Good)
+            .count();
+        assert_eq!(
+            good,
+            spec.good_players(),
+            "{}-player games need {} Good players",
+            roles.len(),
+            spec.good_players()
+        );
+        let mut rng = GameRng::seed_from_u64(seed.into());
+
+        let mut players = Players::new();
+        for (name, role) in roles {
+            players.add_player(name, role);
+        }
+
+        Ok(Game::assign(players, spec, seed, &mut rng))
+    }
+
+    /// Finishes rolling a game once every player has a role, picking the assassin, priority target, and proposal
+    /// order and generating each player's role information.
+    fn assign(players: Players, spec: GameSpec, seed: u32, rng: &mut GameRng) -> Game {
         let assassin = players
             .evil_players()
-            .choose(&mut rng)
+            .choose(rng)
             .cloned()
             .expect("Could not choose an assassin, game contained no evil players");
 
@@ -120,38 +226,54 @@ impl Game {
         if players.has_role(Role::Tristan) && players.has_role(Role::Iseult) {
             priority_targets.push(PriorityTarget::Lovers);
         }
-        // TODO: Guinevere
+        if players.has_role(Role::Guinevere) {
+            priority_targets.push(PriorityTarget::Guinevere);
+        }
+        if players.has_role(Role::Nimue) {
+            priority_targets.push(PriorityTarget::Nimue);
+        }
         let priority_target = priority_targets
-            .choose(&mut rng)
+            .choose(rng)
             .copied()
             .unwrap_or(PriorityTarget::None);
 
+        // Players are stored in a HashMap, so visit them in a fixed order to keep the game reproducible
+        let mut names = players
+            .iter()
+            .map(|player| player.name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+
         let mut info = HashMap::with_capacity(players.len());
-        for player in players.iter() {
+        for name in names.iter() {
+            let player = players.by_name(name).unwrap();
             info.insert(
-                player.name.clone(),
-                player.role.generate_info(
-                    &mut rng,
-                    &player.name,
-                    spec,
-                    &players,
-                    &assassin,
-                    priority_target,
-                ),
+                name.clone(),
+                player
+                    .role
+                    .generate_info(rng, name, &spec, &players, &assassin, priority_target),
             );
         }
 
-        let mut proposal_order = info.keys().cloned().collect::<Vec<_>>();
-        proposal_order.shuffle(&mut rng);
+        let mut proposal_order = names;
+        proposal_order.shuffle(rng);
+
+        log::info!("Rolled game with seed {}", seed);
 
-        Ok(Game {
+        Game {
             players,
             info,
             proposal_order,
             assassin,
             priority_target,
             spec,
-        })
+            seed,
+        }
+    }
+
+    /// The seed this game was rolled from. Rolling the same players and rules with this seed recreates the game.
+    pub fn seed(&self) -> u32 {
+        self.seed
     }
 
     pub fn proposal_order(&self) -> &[String] {
@@ -262,8 +384,10 @@ impl GameSpec {
             3 => Ok(&THREE_PLAYER),
             4 => Ok(&FOUR_PLAYER),
             5 => Ok(&FIVE_PLAYER),
+            6 => Ok(&SIX_PLAYER),
             7 => Ok(&SEVEN_PLAYER),
             8 => Ok(&EIGHT_PLAYER),
+            9 => Ok(&NINE_PLAYER),
             10 => Ok(&TEN_PLAYER),
             _ => Err(CreateGameError::UnsupportedSize(players)),
         }
@@ -286,6 +410,7 @@ impl GameSpec {
     }
 
     /// Tests if `role` is allowed in games of this size
+    #[cfg(test)]
     pub fn has_role(&self, role: Role) -> bool {
         if role.is_evil() {
             self.evil_roles.contains(&role)
@@ -295,128 +420,333 @@ impl GameSpec {
     }
 }
 
+impl TurnTimers {
+    /// Turn timers where no phase has a time limit
+    pub const NONE: TurnTimers = TurnTimers {
+        proposal: None,
+        voting: None,
+        mission: None,
+        discussion: None,
+    };
+
+    /// Whether any phase has a time limit
+    pub fn is_enabled(&self) -> bool {
+        *self != TurnTimers::NONE
+    }
+}
+
 static FIVE_PLAYER: GameSpec = GameSpec {
     players: 5,
     mission_sizes: [2, 3, 2, 3, 3],
-    good_roles: &[
+    good_roles: Cow::Borrowed(&[
         Role::Merlin,
         Role::Lancelot,
         Role::Percival,
         Role::Tristan,
         Role::Iseult,
         Role::Nimue,
-    ],
-    evil_roles: &[Role::Mordred, Role::Morgana, Role::Maelegant, Role::Maeve],
+    ]),
+    evil_roles: Cow::Borrowed(&[Role::Mordred, Role::Morgana, Role::Maelegant, Role::Maeve]),
     good_players: 3,
     max_proposals: 5,
     max_maeve_obscures: 2,
     double_fail_mission_four: false,
+    turn_timers: TurnTimers::NONE,
+};
+
+static SIX_PLAYER: GameSpec = GameSpec {
+    players: 6,
+    mission_sizes: [2, 3, 4, 3, 4],
+    good_roles: Cow::Borrowed(&[
+        Role::Merlin,
+        Role::Lancelot,
+        Role::Percival,
+        Role::Tristan,
+        Role::Iseult,
+        Role::Nimue,
+    ]),
+    evil_roles: Cow::Borrowed(&[Role::Mordred, Role::Morgana, Role::Maelegant, Role::Maeve]),
+    good_players: 4,
+    max_proposals: 6,
+    max_maeve_obscures: 2,
+    double_fail_mission_four: false,
+    turn_timers: TurnTimers::NONE,
 };
 
 static SEVEN_PLAYER: GameSpec = GameSpec {
     players: 7,
     mission_sizes: [2, 3, 3, 4, 4],
-    good_roles: &[
+    good_roles: Cow::Borrowed(&[
         Role::Merlin,
         Role::Lancelot,
         Role::Percival,
         Role::Tristan,
         Role::Iseult,
         Role::Nimue,
-    ],
-    evil_roles: &[
+        Role::Guinevere,
+        Role::Titania,
+    ]),
+    evil_roles: Cow::Borrowed(&[
         Role::Mordred,
         Role::Morgana,
         Role::Maelegant,
         Role::Maeve,
         Role::Agravaine,
-    ],
+    ]),
     good_players: 4,
     max_proposals: 7,
     max_maeve_obscures: 3,
     double_fail_mission_four: true,
+    turn_timers: TurnTimers::NONE,
 };
 
 static EIGHT_PLAYER: GameSpec = GameSpec {
     players: 8,
     mission_sizes: [3, 4, 4, 5, 5],
-    good_roles: &[
+    good_roles: Cow::Borrowed(&[
         Role::Merlin,
         Role::Lancelot,
         Role::Percival,
         Role::Tristan,
         Role::Iseult,
         Role::Arthur,
-    ],
-    evil_roles: &[
+        Role::Guinevere,
+        Role::Titania,
+    ]),
+    evil_roles: Cow::Borrowed(&[
         Role::Mordred,
         Role::Morgana,
         Role::Maelegant,
         Role::Maeve,
         Role::Agravaine,
-    ],
+    ]),
     good_players: 5,
     max_proposals: 10,
     max_maeve_obscures: 3,
     double_fail_mission_four: true,
+    turn_timers: TurnTimers::NONE,
+};
+
+static NINE_PLAYER: GameSpec = GameSpec {
+    players: 9,
+    mission_sizes: [3, 4, 4, 5, 5],
+    good_roles: Cow::Borrowed(&[
+        Role::Merlin,
+        Role::Lancelot,
+        Role::Percival,
+        Role::Tristan,
+        Role::Iseult,
+        Role::Arthur,
+        Role::Guinevere,
+        Role::Titania,
+    ]),
+    evil_roles: Cow::Borrowed(&[
+        Role::Mordred,
+        Role::Morgana,
+        Role::Maelegant,
+        Role::Maeve,
+        Role::Agravaine,
+    ]),
+    good_players: 6,
+    max_proposals: 12,
+    max_maeve_obscures: 3,
+    double_fail_mission_four: true,
+    turn_timers: TurnTimers::NONE,
 };
 
 static TEN_PLAYER: GameSpec = GameSpec {
     players: 10,
     mission_sizes: [3, 4, 4, 5, 5],
-    good_roles: &[
+    good_roles: Cow::Borrowed(&[
         Role::Merlin,
         Role::Lancelot,
         Role::Percival,
         Role::Tristan,
         Role::Iseult,
         Role::Arthur,
-    ],
-    evil_roles: &[
+        Role::Guinevere,
+        Role::Titania,
+    ]),
+    evil_roles: Cow::Borrowed(&[
         Role::Mordred,
         Role::Morgana,
         Role::Maelegant,
         Role::Maeve,
         Role::Agravaine,
         Role::Colgrevance,
-    ],
+    ]),
     good_players: 6,
     max_proposals: 14,
     max_maeve_obscures: 4,
     double_fail_mission_four: true,
+    turn_timers: TurnTimers::NONE,
 };
 
 /// Two-player games, for testing
 static TWO_PLAYER: GameSpec = GameSpec {
     players: 2,
     mission_sizes: [1, 1, 2, 2, 2],
-    good_roles: Role::ALL_GOOD,
-    evil_roles: Role::ALL_EVIL,
+    good_roles: Cow::Borrowed(Role::ALL_GOOD),
+    evil_roles: Cow::Borrowed(Role::ALL_EVIL),
     good_players: 1,
     max_proposals: 2,
     max_maeve_obscures: 2,
     double_fail_mission_four: false,
+    turn_timers: TurnTimers::NONE,
 };
 
 /// Three-player games, for testing
 static THREE_PLAYER: GameSpec = GameSpec {
     players: 3,
     mission_sizes: [1, 2, 2, 2, 3],
-    good_roles: Role::ALL_GOOD,
-    evil_roles: Role::ALL_EVIL,
+    good_roles: Cow::Borrowed(Role::ALL_GOOD),
+    evil_roles: Cow::Borrowed(Role::ALL_EVIL),
     good_players: 2,
     max_proposals: 3,
     max_maeve_obscures: 2,
     double_fail_mission_four: false,
+    turn_timers: TurnTimers::NONE,
 };
 
 static FOUR_PLAYER: GameSpec = GameSpec {
     players: 4,
     mission_sizes: [2, 2, 3, 3, 4],
-    good_roles: Role::ALL_GOOD,
-    evil_roles: Role::ALL_EVIL,
+    good_roles: Cow::Borrowed(Role::ALL_GOOD),
+    evil_roles: Cow::Borrowed(Role::ALL_EVIL),
     good_players: 2,
     max_proposals: 4,
     max_maeve_obscures: 2,
     double_fail_mission_four: true,
+    turn_timers: TurnTimers::NONE,
 };
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_roll_supported_sizes() {
+        for size in 2..=10 {
+            let names = (0..size)
+                .map(|i| format!("Player {}", i))
+                .collect::<Vec<_>>();
+            let game = Game::roll(names, &Ruleset::default()).unwrap();
+            let spec = GameSpec::for_players(size).unwrap();
+
+            assert_eq!(game.size(), size);
+            assert_eq!(game.players.good_players().len(), spec.good_players());
+            assert_eq!(game.players.evil_players().len(), spec.evil_players());
+            for player in game.players.iter() {
+                assert!(spec.has_role(player.role));
+            }
+        }
+
+        assert!(Game::roll(vec!["Alice".to_string()], &Ruleset::default()).is_err());
+        assert!(Game::roll(
+            (0..11).map(|i| i.to_string()).collect(),
+            &Ruleset::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_roll_with_seed() {
+        let names = (0..8).map(|i| format!("Player {}", i)).collect::<Vec<_>>();
+        let mut reversed = names.clone();
+        reversed.reverse();
+
+        let game = Game::roll_with_seed(names.clone(), &Ruleset::default(), 1234).unwrap();
+        let same = Game::roll_with_seed(reversed, &Ruleset::default(), 1234).unwrap();
+        assert_eq!(game.seed(), 1234);
+        assert_eq!(game.info, same.info);
+        assert_eq!(game.proposal_order, same.proposal_order);
+        assert_eq!(game.assassin, same.assassin);
+        assert_eq!(game.priority_target, same.priority_target);
+
+        let different = (0..10)
+            .map(|seed| Game::roll_with_seed(names.clone(), &Ruleset::default(), seed).unwrap())
+            .any(|other| other.info != game.info || other.proposal_order != game.proposal_order);
+        assert!(different);
+    }
+
+    #[test]
+    fn test_priority_target_combinations() {
+        let targets = [
+            (PriorityTarget::Merlin, &[Role::Merlin][..]),
+            (PriorityTarget::Lovers, &[Role::Tristan, Role::Iseult][..]),
+            (PriorityTarget::Guinevere, &[Role::Guinevere][..]),
+            (PriorityTarget::Nimue, &[Role::Nimue][..]),
+        ];
+
+        for combination in 0..(1 << targets.len()) {
+            let included = targets
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| combination & (1 << i) != 0)
+                .map(|(_, target)| *target)
+                .collect::<Vec<_>>();
+            // Pad the Good team with roles that aren't priority targets, then size the game to fit it
+            let good = included
+                .iter()
+                .flat_map(|(_, roles)| roles.iter().copied())
+                .chain(vec![
+                    Role::Percival,
+                    Role::Lancelot,
+                    Role::Arthur,
+                    Role::Titania,
+                ])
+                .take(6)
+                .collect::<Vec<_>>();
+            let players = (2..=10)
+                .find(|&size| GameSpec::for_players(size).unwrap().good_players() == good.len())
+                .unwrap();
+            let evil = [Role::Mordred, Role::Morgana, Role::Maeve, Role::Agravaine];
+            let evil_players = players - good.len();
+            let roles = good
+                .into_iter()
+                .chain(evil.iter().copied().take(evil_players))
+                .enumerate()
+                .map(|(i, role)| (format!("Player {}", i), role))
+                .collect::<Vec<_>>();
+
+            let mut expected = included
+                .iter()
+                .map(|(target, _)| format!("{:?}", target))
+                .collect::<Vec<_>>();
+            if expected.is_empty() {
+                expected.push(format!("{:?}", PriorityTarget::None));
+            }
+            expected.sort();
+
+            let mut rolled = (0..50)
+                .map(|seed| {
+                    let game = Game::with_roles(roles.clone(), &Ruleset::default(), seed).unwrap();
+                    format!("{:?}", game.priority_target)
+                })
+                .collect::<Vec<_>>();
+            rolled.sort();
+            rolled.dedup();
+            assert_eq!(rolled, expected, "Priority targets for {:?}", roles);
+        }
+    }
+
+    #[test]
+    fn test_with_roles() {
+        let roles = vec![
+            ("Alice".to_string(), Role::Merlin),
+            ("Bob".to_string(), Role::Percival),
+            ("Charlie".to_string(), Role::Lancelot),
+            ("Dana".to_string(), Role::Mordred),
+            ("Eve".to_string(), Role::Morgana),
+        ];
+        let game = Game::with_roles(roles.clone(), &Ruleset::default(), 42).unwrap();
+        let same = Game::with_roles(roles, &Ruleset::default(), 42).unwrap();
+
+        assert!(game.players.is("Alice", Role::Merlin));
+        assert!(game.players.is("Eve", Role::Morgana));
+        assert_eq!(game.priority_target, PriorityTarget::Merlin);
+        assert!(game.assassin == "Dana" || game.assassin == "Eve");
+        assert_eq!(game.info, same.info);
+        assert_eq!(game.proposal_order, same.proposal_order);
+    }
+}
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use super::{Card, GameSpec, Player, Players};
 
 /// A THavalon role
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Role {
     // "Good" roles
     Merlin,
@@ -16,6 +16,8 @@ pub enum Role {
     Iseult,
     Nimue,
     Arthur,
+    Guinevere,
+    Titania,
 
     // "Misunderstood" roles
     Colgrevance,
@@ -26,7 +28,7 @@ pub enum Role {
     Agravaine,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Team {
     Good,
     /// "Misunderstood"
@@ -63,6 +65,29 @@ impl RoleDetails {
     pub fn get_role(&self) -> Role {
         self.role
     }
+
+    pub fn get_team(&self) -> Team {
+        self.team
+    }
+
+    pub fn get_seen_players(&self) -> &[String] {
+        &self.seen_players
+    }
+
+    pub fn get_team_members(&self) -> &[String] {
+        &self.team_members
+    }
+
+    pub fn get_priority_target(&self) -> Option<PriorityTarget> {
+        self.priority_target
+    }
+
+    /// Reveals `player` partway through the game, so this player sees them from now on.
+    pub fn reveal(&mut self, player: &str) {
+        if !self.seen_players.iter().any(|seen| seen == player) {
+            self.seen_players.push(player.to_string());
+        }
+    }
 }
 
 /// A priority assassination target. If the Good team passes 3 missions, then the Assassin must correctly identify
@@ -72,6 +97,7 @@ pub enum PriorityTarget {
     Merlin,
     Lovers,
     Guinevere,
+    Nimue,
     None,
 }
 
@@ -85,6 +111,8 @@ impl Role {
         Role::Iseult,
         Role::Nimue,
         Role::Arthur,
+        Role::Guinevere,
+        Role::Titania,
     ];
 
     /// All Evil roles
@@ -99,7 +127,8 @@ impl Role {
     pub fn is_good(self) -> bool {
         use Role::*;
         match self {
-            Merlin | Lancelot | Percival | Tristan | Iseult | Nimue | Arthur => true,
+            Merlin | Lancelot | Percival | Tristan | Iseult | Nimue | Arthur | Guinevere
+            | Titania => true,
             Colgrevance | Mordred | Morgana | Maelegant | Maeve | Agravaine => false,
         }
     }
@@ -108,6 +137,11 @@ impl Role {
         !self.is_good()
     }
 
+    /// Whether or not the role can play the Questing Beast on missions. Only Good players can track it down.
+    pub fn can_play_questing_beast(self) -> bool {
+        self.is_good()
+    }
+
     pub fn team(self) -> Team {
         if self.is_good() {
             Team::Good
@@ -121,7 +155,10 @@ impl Role {
     }
 
     pub fn is_assassinatable(self) -> bool {
-        matches!(self, Role::Merlin | Role::Tristan | Role::Iseult)
+        matches!(
+            self,
+            Role::Merlin | Role::Tristan | Role::Iseult | Role::Nimue | Role::Guinevere
+        )
     }
 
     pub fn can_play(self, card: Card) -> bool {
@@ -186,8 +223,13 @@ impl Role {
                         seen_players.push(players.by_role(Role::Iseult).unwrap().name.clone());
                         seen_players.push(players.by_role(Role::Tristan).unwrap().name.clone());
                     }
+                    PriorityTarget::Guinevere => {
+                        seen_players.push(players.by_role(Role::Guinevere).unwrap().name.clone());
+                    }
+                    PriorityTarget::Nimue => {
+                        seen_players.push(players.by_role(Role::Nimue).unwrap().name.clone());
+                    }
                     PriorityTarget::None => (),
-                    other => panic!("Unsupported priority target {:?}", other),
                 }
             }
             Role::Tristan | Role::Iseult => {
@@ -215,6 +257,24 @@ impl Role {
                         .map(|player| player.role.to_string()),
                 );
             }
+            Role::Guinevere => {
+                let _ = writeln!(
+                    &mut description,
+                    "You see Lancelot and Arthur, but not which is which."
+                );
+                seen_players.extend(
+                    players
+                        .iter()
+                        .filter(|player| matches!(player.role, Role::Lancelot | Role::Arthur))
+                        .map(|player| player.name.clone()),
+                );
+            }
+            Role::Titania => {
+                let _ = writeln!(
+                    &mut description,
+                    "You appear as Evil to the Evil team, except for Colgrevance."
+                );
+            }
             Role::Mordred => {
                 let _ = writeln!(&mut description, "You are hidden from Merlin.");
             }
@@ -236,7 +296,7 @@ impl Role {
                 let _ = writeln!(&mut abilities, "You may declare to fail a mission you were on that would have otherwise succeeded.");
             }
             Role::Colgrevance => {
-                let _ = writeln!(&mut description, "You know the exact roles of everyone on the evil team and may manipulate them like the puppet master you are.");
+                let _ = writeln!(&mut description, "You know the exact roles of everyone on the evil team and may manipulate them like the puppet master you are. The rest of the evil team does not know you are evil.");
                 seen_players.extend(
                     players
                         .iter()
@@ -246,16 +306,37 @@ impl Role {
             }
         }
 
-        // Make sure the order of seen players doesn't leak info
+        // Make sure the order of seen players doesn't leak info. Sorting first means the order only depends on `rng`,
+        // which keeps seeded games reproducible.
+        seen_players.sort();
         seen_players.shuffle(rng);
 
         let team_members = if self.is_evil() {
-            players
+            // Colgrevance is hidden from the rest of the Evil team, but still sees all of it
+            let hidden = if self == Role::Colgrevance {
+                None
+            } else {
+                players
+                    .by_role(Role::Colgrevance)
+                    .map(|colgrevance| colgrevance.name.as_str())
+            };
+            let mut team_members = players
                 .evil_players()
                 .iter()
-                .filter(|player| **player != me)
+                .filter(|player| **player != me && Some(player.as_str()) != hidden)
                 .cloned()
-                .collect()
+                .collect::<Vec<_>>();
+
+            // Titania appears Evil to everyone on the Evil team except Colgrevance
+            if self != Role::Colgrevance {
+                if let Some(titania) = players.by_role(Role::Titania) {
+                    team_members.push(titania.name.clone());
+                }
+            }
+
+            // As with seen players, the order of team members shouldn't reveal who Titania is
+            team_members.shuffle(rng);
+            team_members
         } else {
             Vec::new()
         };
@@ -272,9 +353,8 @@ impl Role {
             other_info,
             assassinatable: self.is_assassinatable(),
             is_assassin,
-            // In 5-player games, knowing the priority target is too powerful. For example, if the Good team is Lancelot and Lovers, and Lancelot
-            // reverses, it immediately tells the Assassin who the Lovers are.
-            // (We check against 6 players for future-proofing)
+            // In 5- and 6-player games, knowing the priority target is too powerful. For example, if the Good team is Lancelot and Lovers,
+            // and Lancelot reverses, it immediately tells the Assassin who the Lovers are.
             priority_target: if is_assassin && spec.players > 6 {
                 Some(priority_target)
             } else {
@@ -292,11 +372,21 @@ impl fmt::Display for Role {
 }
 
 impl PriorityTarget {
+    /// All priority targets, including no target
+    pub const ALL: &'static [PriorityTarget] = &[
+        PriorityTarget::Merlin,
+        PriorityTarget::Lovers,
+        PriorityTarget::Guinevere,
+        PriorityTarget::Nimue,
+        PriorityTarget::None,
+    ];
+
     /// Checks if assassinating `player` as this target is correct.
     pub fn matches(self, player: &Player) -> bool {
         match self {
             PriorityTarget::Merlin => player.role == Role::Merlin,
-            PriorityTarget::Guinevere => todo!("Need a Guinevere role"),
+            PriorityTarget::Guinevere => player.role == Role::Guinevere,
+            PriorityTarget::Nimue => player.role == Role::Nimue,
             PriorityTarget::Lovers => player.role.is_lover(),
             PriorityTarget::None => false,
         }
@@ -311,3 +401,311 @@ impl PriorityTarget {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::super::{Game, GameSpec, Players, Ruleset};
+    use super::*;
+
+    fn players(roles: &[(&str, Role)]) -> Players {
+        let mut players = Players::new();
+        for (name, role) in roles {
+            players.add_player(name.to_string(), *role);
+        }
+        players
+    }
+
+    #[test]
+    fn test_guinevere_priority_target() {
+        let spec = GameSpec::for_players(7).unwrap();
+        let players = players(&[
+            ("Alice", Role::Guinevere),
+            ("Bob", Role::Percival),
+            ("Charlie", Role::Lancelot),
+            ("Dana", Role::Merlin),
+            ("Eve", Role::Mordred),
+            ("Fred", Role::Maeve),
+            ("Greg", Role::Agravaine),
+        ]);
+        let mut rng = thread_rng();
+
+        let percival = Role::Percival.generate_info(
+            &mut rng,
+            "Bob",
+            spec,
+            &players,
+            "Eve",
+            PriorityTarget::Guinevere,
+        );
+        assert_eq!(percival.seen_players, vec!["Alice".to_string()]);
+
+        let guinevere = Role::Guinevere.generate_info(
+            &mut rng,
+            "Alice",
+            spec,
+            &players,
+            "Eve",
+            PriorityTarget::Guinevere,
+        );
+        assert!(guinevere.assassinatable);
+        assert_eq!(guinevere.seen_players, vec!["Charlie".to_string()]);
+
+        let assassin = Role::Mordred.generate_info(
+            &mut rng,
+            "Eve",
+            spec,
+            &players,
+            "Eve",
+            PriorityTarget::Guinevere,
+        );
+        assert_eq!(assassin.priority_target, Some(PriorityTarget::Guinevere));
+
+        assert!(PriorityTarget::Guinevere.matches(players.by_name("Alice").unwrap()));
+        assert!(!PriorityTarget::Guinevere.matches(players.by_name("Dana").unwrap()));
+    }
+
+    #[test]
+    fn test_nimue_priority_target() {
+        let spec = GameSpec::for_players(7).unwrap();
+        let players = players(&[
+            ("Alice", Role::Nimue),
+            ("Bob", Role::Percival),
+            ("Charlie", Role::Lancelot),
+            ("Dana", Role::Merlin),
+            ("Eve", Role::Mordred),
+            ("Fred", Role::Maeve),
+            ("Greg", Role::Agravaine),
+        ]);
+        let mut rng = thread_rng();
+
+        let percival = Role::Percival.generate_info(
+            &mut rng,
+            "Bob",
+            spec,
+            &players,
+            "Eve",
+            PriorityTarget::Nimue,
+        );
+        assert_eq!(percival.seen_players, vec!["Alice".to_string()]);
+
+        let nimue = Role::Nimue.generate_info(
+            &mut rng,
+            "Alice",
+            spec,
+            &players,
+            "Eve",
+            PriorityTarget::Nimue,
+        );
+        assert!(nimue.assassinatable);
+
+        let assassin = Role::Mordred.generate_info(
+            &mut rng,
+            "Eve",
+            spec,
+            &players,
+            "Eve",
+            PriorityTarget::Nimue,
+        );
+        assert_eq!(assassin.priority_target, Some(PriorityTarget::Nimue));
+
+        assert!(PriorityTarget::Nimue.matches(players.by_name("Alice").unwrap()));
+        assert!(!PriorityTarget::Nimue.matches(players.by_name("Dana").unwrap()));
+        assert_eq!(PriorityTarget::Nimue.expected_targets(), 1);
+    }
+
+    #[test]
+    fn test_titania_appears_evil() {
+        let spec = GameSpec::for_players(10).unwrap();
+        let players = players(&[
+            ("Alice", Role::Titania),
+            ("Bob", Role::Merlin),
+            ("Charlie", Role::Percival),
+            ("Dana", Role::Lancelot),
+            ("Eve", Role::Tristan),
+            ("Fred", Role::Iseult),
+            ("Greg", Role::Mordred),
+            ("Hannah", Role::Morgana),
+            ("Ivan", Role::Maeve),
+            ("Jess", Role::Colgrevance),
+        ]);
+        let mut rng = thread_rng();
+
+        let team_members = |role: Role, me: &str| {
+            let mut team_members = role
+                .generate_info(
+                    &mut thread_rng(),
+                    me,
+                    spec,
+                    &players,
+                    "Greg",
+                    PriorityTarget::Merlin,
+                )
+                .team_members;
+            team_members.sort();
+            team_members
+        };
+
+        assert_eq!(
+            team_members(Role::Mordred, "Greg"),
+            vec!["Alice", "Hannah", "Ivan"]
+        );
+        assert_eq!(
+            team_members(Role::Morgana, "Hannah"),
+            vec!["Alice", "Greg", "Ivan"]
+        );
+        assert_eq!(
+            team_members(Role::Maeve, "Ivan"),
+            vec!["Alice", "Greg", "Hannah"]
+        );
+        // Colgrevance is not fooled by Titania
+        assert_eq!(
+            team_members(Role::Colgrevance, "Jess"),
+            vec!["Greg", "Hannah", "Ivan"]
+        );
+
+        let titania = Role::Titania.generate_info(
+            &mut rng,
+            "Alice",
+            spec,
+            &players,
+            "Greg",
+            PriorityTarget::Merlin,
+        );
+        assert_eq!(titania.team, Team::Good);
+        assert!(titania.team_members.is_empty());
+
+        let merlin = Role::Merlin.generate_info(
+            &mut rng,
+            "Bob",
+            spec,
+            &players,
+            "Greg",
+            PriorityTarget::Merlin,
+        );
+        assert!(!merlin.seen_players.contains(&"Alice".to_string()));
+    }
+
+    /// Whether a player with the role `viewer` should learn that the player with the role `seen` is special, either by
+    /// seeing them or by having them as a team member, in a game with `priority_target`.
+    fn expected_visibility(viewer: Role, seen: Role, priority_target: PriorityTarget) -> bool {
+        use Role::*;
+        match viewer {
+            Merlin => (seen.is_evil() && seen != Mordred) || seen == Lancelot,
+            Percival => {
+                seen == Morgana
+                    || match priority_target {
+                        PriorityTarget::Merlin => seen == Merlin,
+                        PriorityTarget::Lovers => seen.is_lover(),
+                        PriorityTarget::Guinevere => seen == Guinevere,
+                        PriorityTarget::Nimue => seen == Nimue,
+                        PriorityTarget::None => false,
+                    }
+            }
+            Guinevere => matches!(seen, Lancelot | Arthur),
+            Colgrevance => seen.is_evil(),
+            _ if viewer.is_evil() => (seen.is_evil() && seen != Colgrevance) || seen == Titania,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_visibility_matrix() {
+        for size in 2..=10 {
+            let names = (1..=size)
+                .map(|n| format!("Player {}", n))
+                .collect::<Vec<_>>();
+            for seed in 0..20 {
+                let game = Game::roll_with_seed(names.clone(), &Ruleset::default(), seed).unwrap();
+                for viewer in game.players.iter() {
+                    let info = &game.info[&viewer.name];
+                    // Nimue and Arthur see roles rather than players, and Colgrevance sees "<role> - <player>"
+                    let seen_players = match viewer.role {
+                        Role::Nimue | Role::Arthur => Vec::new(),
+                        Role::Colgrevance => info
+                            .seen_players
+                            .iter()
+                            .map(|seen| seen.rsplit(" - ").next().unwrap().to_string())
+                            .collect(),
+                        _ => info.seen_players.clone(),
+                    };
+                    let visible = seen_players
+                        .iter()
+                        .chain(info.team_members.iter())
+                        .collect::<HashSet<_>>();
+
+                    for seen in game.players.iter().filter(|seen| seen.name != viewer.name) {
+                        assert_eq!(
+                            visible.contains(&seen.name),
+                            expected_visibility(viewer.role, seen.role, game.priority_target),
+                            "{:?} seeing {:?} in a {}-player game with priority target {:?}",
+                            viewer.role,
+                            seen.role,
+                            size,
+                            game.priority_target
+                        );
+                    }
+
+                    // Only the assassin learns the priority target, and only in larger games
+                    let expected_target = if viewer.name == game.assassin && size > 6 {
+                        Some(game.priority_target)
+                    } else {
+                        None
+                    };
+                    assert_eq!(info.priority_target, expected_target);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_assassin_priority_target_by_size() {
+        let mut rng = thread_rng();
+        let names = [
+            "Alice", "Bob", "Charlie", "Dana", "Eve", "Fred", "Greg", "Hannah", "Ivan",
+        ];
+        let roles = [
+            Role::Merlin,
+            Role::Percival,
+            Role::Lancelot,
+            Role::Tristan,
+            Role::Iseult,
+            Role::Nimue,
+            Role::Mordred,
+            Role::Morgana,
+            Role::Maeve,
+        ];
+
+        for &size in &[6, 9] {
+            let spec = GameSpec::for_players(size).unwrap();
+            let evil = spec.evil_players();
+            let good = &roles[..spec.good_players()];
+            let evil = &roles[roles.len() - evil..];
+            let players = players(
+                &names
+                    .iter()
+                    .copied()
+                    .zip(good.iter().chain(evil.iter()).copied())
+                    .collect::<Vec<_>>(),
+            );
+            let assassin = players.evil_players()[0].clone();
+            let role = players.by_name(&assassin).unwrap().role;
+
+            let info = role.generate_info(
+                &mut rng,
+                &assassin,
+                spec,
+                &players,
+                &assassin,
+                PriorityTarget::Merlin,
+            );
+            assert!(info.is_assassin);
+            if size == 6 {
+                assert_eq!(info.priority_target, None);
+            } else {
+                assert_eq!(info.priority_target, Some(PriorityTarget::Merlin));
+            }
+        }
+    }
+}
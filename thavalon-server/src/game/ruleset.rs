@@ -0,0 +1,268 @@
+//! Host-configurable house rules. A [`Ruleset`] is chosen when a lobby is created, before the number of players is
+//! known, so every rule is optional and falls back to the default [`GameSpec`] for the final player count.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::role::{Role, Team};
+use super::{CreateGameError, GameSpec, TurnTimers};
+
+/// Custom rules for a game. Unset rules use the defaults for the number of players.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Ruleset {
+    /// The number of players on each mission
+    pub mission_sizes: Option<[usize; 5]>,
+    /// The maximum number of proposals allowed before force activates
+    pub max_proposals: Option<usize>,
+    /// The maximum number of times Maeve can obscure voting results in a game
+    pub max_maeve_obscures: Option<usize>,
+    /// True if mission 4 requires at least two failures
+    pub double_fail_mission_four: Option<bool>,
+    /// Allowed good roles in the game
+    pub good_roles: Option<Vec<Role>>,
+    /// Allowed evil roles in the game
+    pub evil_roles: Option<Vec<Role>>,
+    /// True if spectators may see every player's information once the game is over
+    pub omniscient_spectators: Option<bool>,
+    /// Seconds the proposer has to make a proposal. If unset, there is no time limit.
+    pub proposal_timer: Option<u64>,
+    /// Seconds players have to vote on a proposal. If unset, there is no time limit.
+    pub voting_timer: Option<u64>,
+    /// Seconds players on a mission have to play a card. If unset, there is no time limit.
+    pub mission_timer: Option<u64>,
+    /// Seconds Evil has to discuss the assassination. If unset, discussion lasts until the assassin decides.
+    pub discussion_timer: Option<u64>,
+}
+
+impl Ruleset {
+    /// Whether spectators may see every player's information once the game is over. This defaults to false.
+    pub fn omniscient_spectators(&self) -> bool {
+        self.omniscient_spectators.unwrap_or(false)
+    }
+
+    /// Builds the [`GameSpec`] for a game with `players` players, applying any rules set by the host. This fails if
+    /// the ruleset cannot be used with that many players, for example if a mission is larger than the game.
+    pub fn to_spec(&self, players: usize) -> Result<GameSpec, CreateGameError> {
+        let mut spec = GameSpec::for_players(players)?.clone();
+
+        if let Some(mission_sizes) = self.mission_sizes {
+            spec.mission_sizes = mission_sizes;
+        }
+        if let Some(max_proposals) = self.max_proposals {
+            spec.max_proposals = max_proposals;
+        }
+        if let Some(max_maeve_obscures) = self.max_maeve_obscures {
+            spec.max_maeve_obscures = max_maeve_obscures;
+        }
+        if let Some(double_fail_mission_four) = self.double_fail_mission_four {
+            spec.double_fail_mission_four = double_fail_mission_four;
+        }
+        if let Some(good_roles) = &self.good_roles {
+            spec.good_roles = Cow::Owned(good_roles.clone());
+        }
+        if let Some(evil_roles) = &self.evil_roles {
+            spec.evil_roles = Cow::Owned(evil_roles.clone());
+        }
+        spec.turn_timers = TurnTimers {
+            proposal: timer(self.proposal_timer)?,
+            voting: timer(self.voting_timer)?,
+            mission: timer(self.mission_timer)?,
+            discussion: timer(self.discussion_timer)?,
+        };
+
+        spec.validate()?;
+        Ok(spec)
+    }
+}
+
+impl GameSpec {
+    /// Checks that the rules in this spec are internally consistent, so that a game can be rolled from it.
+    pub fn validate(&self) -> Result<(), CreateGameError> {
+        for (index, &size) in self.mission_sizes.iter().enumerate() {
+            if size == 0 || size > self.players as usize {
+                return Err(CreateGameError::InvalidMissionSize {
+                    mission: index as u8 + 1,
+                    size,
+                });
+            }
+        }
+        if self.max_proposals == 0 {
+            return Err(CreateGameError::InvalidMaxProposals);
+        }
+        if self.double_fail_mission_four && self.mission_size(4) < 2 {
+            return Err(CreateGameError::InvalidDoubleFail(self.mission_size(4)));
+        }
+
+        validate_roles(&self.good_roles, Team::Good, self.good_players())?;
+        validate_roles(&self.evil_roles, Team::Evil, self.evil_players())?;
+        Ok(())
+    }
+}
+
+/// The longest turn timer a ruleset may set, in seconds.
+const MAX_TIMER: u64 = 60 * 60;
+
+/// Converts a turn timer from the ruleset into a [`Duration`]. A timer of 0 seconds would expire before anyone could
+/// act, and anything over [`MAX_TIMER`] is effectively no timer at all, so both are rejected.
+fn timer(seconds: Option<u64>) -> Result<Option<Duration>, CreateGameError> {
+    match seconds {
+        Some(0) => Err(CreateGameError::InvalidTimer),
+        Some(seconds) if seconds > MAX_TIMER => Err(CreateGameError::InvalidTimer),
+        Some(seconds) => Ok(Some(Duration::from_secs(seconds))),
+        None => Ok(None),
+    }
+}
+
+/// Checks that a role pool only contains roles on `team`, has no duplicates, and can fill `needed` players.
+fn validate_roles(roles: &[Role], team: Team, needed: usize) -> Result<(), CreateGameError> {
+    let mut seen = HashSet::with_capacity(roles.len());
+    for &role in roles {
+        if role.team() != team {
+            return Err(CreateGameError::WrongTeam(role));
+        }
+        if !seen.insert(role) {
+            return Err(CreateGameError::DuplicateRole(role));
+        }
+    }
+
+    if roles.len() < needed {
+        Err(CreateGameError::NotEnoughRoles {
+            team,
+            needed,
+            available: roles.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_ruleset() {
+        for size in 2..=10 {
+            let spec = Ruleset::default().to_spec(size).unwrap();
+            assert_eq!(spec.players as usize, size);
+        }
+        assert!(Ruleset::default().to_spec(11).is_err());
+    }
+
+    #[test]
+    fn test_custom_ruleset() {
+        let ruleset = Ruleset {
+            mission_sizes: Some([2, 2, 3, 3, 3]),
+            max_proposals: Some(3),
+            double_fail_mission_four: Some(true),
+            good_roles: Some(vec![Role::Merlin, Role::Percival, Role::Guinevere]),
+            voting_timer: Some(60),
+            ..Ruleset::default()
+        };
+        let spec = ruleset.to_spec(5).unwrap();
+        assert_eq!(spec.mission_sizes, [2, 2, 3, 3, 3]);
+        assert_eq!(spec.max_proposals, 3);
+        assert_eq!(spec.max_maeve_obscures, 2);
+        assert!(spec.double_fail_mission_four());
+        assert_eq!(
+            spec.good_roles.as_ref(),
+            &[Role::Merlin, Role::Percival, Role::Guinevere]
+        );
+        assert_eq!(
+            spec.turn_timers,
+            TurnTimers {
+                proposal: None,
+                voting: Some(Duration::from_secs(60)),
+                mission: None,
+                discussion: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_ruleset() {
+        let too_large = Ruleset {
+            mission_sizes: Some([2, 3, 2, 3, 6]),
+            ..Ruleset::default()
+        };
+        assert!(matches!(
+            too_large.to_spec(5),
+            Err(CreateGameError::InvalidMissionSize {
+                mission: 5,
+                size: 6
+            })
+        ));
+
+        let too_few_roles = Ruleset {
+            good_roles: Some(vec![Role::Merlin, Role::Percival]),
+            ..Ruleset::default()
+        };
+        assert!(matches!(
+            too_few_roles.to_spec(5),
+            Err(CreateGameError::NotEnoughRoles {
+                team: Team::Good,
+                needed: 3,
+                available: 2
+            })
+        ));
+
+        let wrong_team = Ruleset {
+            evil_roles: Some(vec![Role::Mordred, Role::Merlin]),
+            ..Ruleset::default()
+        };
+        assert!(matches!(
+            wrong_team.to_spec(5),
+            Err(CreateGameError::WrongTeam(Role::Merlin))
+        ));
+
+        let duplicate = Ruleset {
+            evil_roles: Some(vec![Role::Mordred, Role::Mordred]),
+            ..Ruleset::default()
+        };
+        assert!(matches!(
+            duplicate.to_spec(5),
+            Err(CreateGameError::DuplicateRole(Role::Mordred))
+        ));
+
+        let no_proposals = Ruleset {
+            max_proposals: Some(0),
+            ..Ruleset::default()
+        };
+        assert!(matches!(
+            no_proposals.to_spec(5),
+            Err(CreateGameError::InvalidMaxProposals)
+        ));
+
+        let lone_double_fail = Ruleset {
+            mission_sizes: Some([2, 3, 2, 1, 3]),
+            double_fail_mission_four: Some(true),
+            ..Ruleset::default()
+        };
+        assert!(matches!(
+            lone_double_fail.to_spec(5),
+            Err(CreateGameError::InvalidDoubleFail(1))
+        ));
+
+        let instant_timer = Ruleset {
+            mission_timer: Some(0),
+            ..Ruleset::default()
+        };
+        assert!(matches!(
+            instant_timer.to_spec(5),
+            Err(CreateGameError::InvalidTimer)
+        ));
+
+        let endless_timer = Ruleset {
+            proposal_timer: Some(u64::MAX),
+            ..Ruleset::default()
+        };
+        assert!(matches!(
+            endless_timer.to_spec(5),
+            Err(CreateGameError::InvalidTimer)
+        ));
+    }
+}
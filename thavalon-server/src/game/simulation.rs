@@ -0,0 +1,438 @@
+//! Headless simulation of complete games between [`Bot`]s, for checking how balanced a ruleset is. Games run directly
+//! on the [`GameStateWrapper`] state machine, without any networking, database, or async runtime.
+//!
+//! Run with `thavalon-server simulate [--players N] [--ruleset FILE] [--games N] [--seed N] [--format json|csv]`.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::fs;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use super::bot::Bot;
+use super::messages::{Action, Message};
+use super::role::{PriorityTarget, Role, Team};
+use super::state::{Effect, GameStateWrapper};
+use super::{CreateGameError, Game, Ruleset};
+
+/// Upper bound on the number of actions in one game, in case the bots get stuck in a loop.
+const MAX_ACTIONS: usize = 10_000;
+
+/// Settings for a batch of simulated games.
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub players: usize,
+    pub ruleset: Ruleset,
+    pub games: usize,
+    /// Seed for the first game. Each game after that uses the next seed.
+    pub seed: u32,
+}
+
+/// How a game was decided.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub enum WinCondition {
+    /// Three missions failed
+    Missions,
+    /// The game ended with an assassination attempt, whether or not it was correct
+    Assassination,
+}
+
+/// The result of one simulated game.
+#[derive(Debug, Clone)]
+pub struct GameOutcome {
+    pub winning_team: Team,
+    pub win_condition: WinCondition,
+    pub priority_target: PriorityTarget,
+    pub roles: HashMap<String, Role>,
+}
+
+/// Wins out of games played, for some subset of games or players.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WinRate {
+    pub games: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+}
+
+/// Aggregate results of a batch of simulated games.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationReport {
+    pub players: usize,
+    pub games: usize,
+    /// Games that could not be finished, for example because the bots got stuck
+    pub unfinished: usize,
+    /// Win rates for each team
+    pub teams: BTreeMap<String, WinRate>,
+    /// Win rates for the player holding each role
+    pub roles: BTreeMap<String, WinRate>,
+    /// Good's win rate for each priority target
+    pub priority_targets: BTreeMap<String, WinRate>,
+    /// How often each team won by each condition, out of all finished games
+    pub win_conditions: BTreeMap<String, WinRate>,
+}
+
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Could not read ruleset: {0}")]
+    Ruleset(String),
+    #[error("Could not create game: {0}")]
+    CreateGame(#[from] CreateGameError),
+    #[error("Game {seed} did not finish")]
+    Unfinished { seed: u32 },
+}
+
+/// Output formats for a [`SimulationReport`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+/// Entry point for the `simulate` command. `args` are the command-line arguments after `simulate`.
+pub fn run(args: &[String]) -> Result<(), SimulationError> {
+    let (config, format) = parse_args(args)?;
+    let report = simulate(&config)?;
+    match format {
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report).unwrap()),
+        ReportFormat::Csv => print!("{}", report.to_csv()),
+    }
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<(SimulationConfig, ReportFormat), SimulationError> {
+    let mut config = SimulationConfig {
+        players: 7,
+        ruleset: Ruleset::default(),
+        games: 1000,
+        seed: 0,
+    };
+    let mut format = ReportFormat::Json;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| SimulationError::InvalidArgument(format!("{} needs a value", flag)))?;
+        let invalid = || SimulationError::InvalidArgument(format!("{} {}", flag, value));
+        match flag.as_str() {
+            "--players" => config.players = value.parse().map_err(|_| invalid())?,
+            "--games" => config.games = value.parse().map_err(|_| invalid())?,
+            "--seed" => config.seed = value.parse().map_err(|_| invalid())?,
+            "--ruleset" => {
+                let ruleset = fs::read_to_string(value)
+                    .map_err(|e| SimulationError::Ruleset(e.to_string()))?;
+                config.ruleset = serde_json::from_str(&ruleset)
+                    .map_err(|e| SimulationError::Ruleset(e.to_string()))?;
+            }
+            "--format" => {
+                format = match value.as_str() {
+                    "json" => ReportFormat::Json,
+                    "csv" => ReportFormat::Csv,
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(SimulationError::InvalidArgument(flag.clone())),
+        }
+    }
+
+    Ok((config, format))
+}
+
+/// Simulates `config.games` games and aggregates the results. Games that don't finish are counted separately rather
+/// than failing the whole batch, but an invalid ruleset is an error.
+pub fn simulate(config: &SimulationConfig) -> Result<SimulationReport, SimulationError> {
+    config.ruleset.to_spec(config.players)?;
+
+    let mut report = SimulationReport {
+        players: config.players,
+        ..SimulationReport::default()
+    };
+    for game in 0..config.games {
+        let seed = config.seed.wrapping_add(game as u32);
+        match play_game(config.players, &config.ruleset, seed) {
+            Ok(outcome) => report.add(&outcome),
+            Err(SimulationError::Unfinished { seed }) => {
+                log::warn!("Simulated game {} did not finish", seed);
+                report.unfinished += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    report.finish();
+    Ok(report)
+}
+
+/// Plays one game between bots, rolled from `seed`.
+pub fn play_game(
+    players: usize,
+    ruleset: &Ruleset,
+    seed: u32,
+) -> Result<GameOutcome, SimulationError> {
+    let names = (1..=players)
+        .map(|n| format!("Bot {}", n))
+        .collect::<Vec<_>>();
+    let game = Game::roll_with_seed(names.clone(), ruleset, seed)?;
+    let priority_target = game.priority_target;
+    let roles = game
+        .players
+        .iter()
+        .map(|player| (player.name.clone(), player.role))
+        .collect();
+
+    let mut table = Table::new(names, seed);
+    let (mut state, effects) = GameStateWrapper::new(game);
+    table.deliver(None, effects);
+
+    for _ in 0..MAX_ACTIONS {
+        if state.is_done() {
+            break;
+        }
+
+        let (next_state, effects, actor) = if let Some((player, action)) = table.actions.pop_front()
+        {
+            let (next_state, effects) = state.handle_action(&player, action);
+            (next_state, effects, Some(player))
+        } else if table.timeout {
+            // Nobody has anything left to do, so let the timeout expire right away
+            table.timeout = false;
+            let (next_state, effects) = state.handle_timeout();
+            (next_state, effects, None)
+        } else {
+            break;
+        };
+        table.deliver(actor.as_deref(), effects);
+        state = next_state;
+    }
+
+    match table.winning_team {
+        Some(winning_team) => Ok(GameOutcome {
+            winning_team,
+            win_condition: if table.assassinated {
+                WinCondition::Assassination
+            } else {
+                WinCondition::Missions
+            },
+            priority_target,
+            roles,
+        }),
+        None => Err(SimulationError::Unfinished { seed }),
+    }
+}
+
+/// The bots playing a simulated game, and the actions they've queued up.
+struct Table {
+    bots: HashMap<String, Bot>,
+    actions: VecDeque<(String, Action)>,
+    /// Whether a timeout is active
+    timeout: bool,
+    winning_team: Option<Team>,
+    /// Whether an assassination was attempted
+    assassinated: bool,
+}
+
+impl Table {
+    fn new(names: Vec<String>, seed: u32) -> Table {
+        let bots = names
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let bot_seed = (seed << 8) + index as u32;
+                (name.clone(), Bot::new(name, bot_seed))
+            })
+            .collect();
+        Table {
+            bots,
+            actions: VecDeque::new(),
+            timeout: false,
+            winning_team: None,
+            assassinated: false,
+        }
+    }
+
+    /// Applies the effects of a state transition, delivering messages to bots and queueing up their responses.
+    /// `actor` is the bot whose action caused the effects, if any.
+    fn deliver(&mut self, actor: Option<&str>, effects: Vec<Effect>) {
+        for effect in effects {
+            match effect {
+                Effect::Broadcast(message) => {
+                    self.observe(&message);
+                    let mut names = self.bots.keys().cloned().collect::<Vec<_>>();
+                    // Deliver in a fixed order so games are reproducible
+                    names.sort();
+                    for name in names {
+                        self.send_to(&name, &message);
+                    }
+                }
+                Effect::Send(player, message) => self.send_to(&player, &message),
+                Effect::Reply(message) => {
+                    if let Some(actor) = actor {
+                        self.send_to(actor, &message);
+                    }
+                }
+                Effect::StartTimeout(_) => self.timeout = true,
+                Effect::ClearTimeout => self.timeout = false,
+            }
+        }
+    }
+
+    fn send_to(&mut self, player: &str, message: &Message) {
+        let bot = self.bots.get_mut(player).expect("Message for unknown bot");
+        for action in bot.on_message(message) {
+            self.actions.push_back((player.to_string(), action));
+        }
+    }
+
+    /// Keeps track of how the game ends from public messages.
+    fn observe(&mut self, message: &Message) {
+        match message {
+            Message::AssassinationResult { .. } => self.assassinated = true,
+            Message::GameOver { winning_team, .. } => self.winning_team = Some(*winning_team),
+            _ => (),
+        }
+    }
+}
+
+impl SimulationReport {
+    /// Adds the result of one game to the report.
+    fn add(&mut self, outcome: &GameOutcome) {
+        self.games += 1;
+
+        for team in [Team::Good, Team::Evil].iter() {
+            self.teams
+                .entry(format!("{:?}", team))
+                .or_default()
+                .record(outcome.winning_team == *team);
+        }
+
+        for role in outcome.roles.values() {
+            self.roles
+                .entry(format!("{:?}", role))
+                .or_default()
+                .record(outcome.winning_team == role.team());
+        }
+
+        self.priority_targets
+            .entry(format!("{:?}", outcome.priority_target))
+            .or_default()
+            .record(outcome.winning_team == Team::Good);
+
+        let condition = format!("{:?} by {:?}", outcome.winning_team, outcome.win_condition);
+        self.win_conditions.entry(condition).or_default().wins += 1;
+    }
+
+    /// Computes win rates once all games have been added.
+    fn finish(&mut self) {
+        for rate in self
+            .teams
+            .values_mut()
+            .chain(self.roles.values_mut())
+            .chain(self.priority_targets.values_mut())
+        {
+            rate.finish();
+        }
+        for rate in self.win_conditions.values_mut() {
+            rate.games = self.games;
+            rate.finish();
+        }
+    }
+
+    /// Formats the report as CSV, with one row per win rate.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("category,name,games,wins,win_rate\n");
+        let categories = [
+            ("team", &self.teams),
+            ("role", &self.roles),
+            ("priority_target", &self.priority_targets),
+            ("win_condition", &self.win_conditions),
+        ];
+        for (category, rates) in categories.iter() {
+            for (name, rate) in rates.iter() {
+                csv.push_str(&format!("{},{},{}\n", category, name, rate));
+            }
+        }
+        csv
+    }
+}
+
+impl WinRate {
+    fn record(&mut self, won: bool) {
+        self.games += 1;
+        if won {
+            self.wins += 1;
+        }
+    }
+
+    fn finish(&mut self) {
+        self.win_rate = if self.games == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.games as f64
+        };
+    }
+}
+
+impl fmt::Display for WinRate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{:.4}", self.games, self.wins, self.win_rate)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_simulate() {
+        let config = SimulationConfig {
+            players: 7,
+            ruleset: Ruleset::default(),
+            games: 20,
+            seed: 1,
+        };
+        let report = simulate(&config).unwrap();
+        assert_eq!(report.games + report.unfinished, 20);
+        assert_eq!(report.teams["Good"].games, report.games);
+        assert_eq!(
+            report.teams["Good"].wins + report.teams["Evil"].wins,
+            report.games
+        );
+        let conditions = report.win_conditions.values().map(|rate| rate.wins);
+        assert_eq!(conditions.sum::<usize>(), report.games);
+        assert_eq!(
+            report.roles.values().map(|rate| rate.games).sum::<usize>(),
+            report.games * 7
+        );
+    }
+
+    #[test]
+    fn test_play_game_is_reproducible() {
+        let ruleset = Ruleset::default();
+        for seed in 0..5 {
+            let first = play_game(8, &ruleset, seed).unwrap();
+            let second = play_game(8, &ruleset, seed).unwrap();
+            assert_eq!(first.winning_team, second.winning_team);
+            assert_eq!(first.win_condition, second.win_condition);
+            assert_eq!(first.roles, second.roles);
+        }
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = ["--players", "5", "--games", "10", "--format", "csv"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>();
+        let (config, format) = parse_args(&args).unwrap();
+        assert_eq!(config.players, 5);
+        assert_eq!(config.games, 10);
+        assert_eq!(format, ReportFormat::Csv);
+
+        let bad = vec!["--players".to_string()];
+        assert!(parse_args(&bad).is_err());
+    }
+}
@@ -1,31 +1,155 @@
 //! Snapshots of the current state of a game. These can be persisted or sent to clients.
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use serde::Serialize;
 use thiserror::Error;
+use tokio::sync::broadcast;
 
 use super::interactions::Interactions;
-use super::messages::{Action, GameError, Message};
+use super::messages::{Action, GameError, LegalAction, Message, Nomination, VoteCounts};
 use super::role::RoleDetails;
+use super::MissionNumber;
 
-/// Snapshot of game state.
+/// Snapshot of game state. Clients can render the game from the structured state without replaying the log.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GameSnapshot {
     pub me: String,
     pub role_info: Option<RoleDetails>,
+    /// Whether the game is currently paused
+    pub paused: bool,
+    pub phase: SnapshotPhase,
+    pub proposal_order: Vec<String>,
+    /// The mission currently being proposed or played
+    pub mission: MissionNumber,
+    /// The number of players going on the current mission
+    pub mission_size: usize,
+    /// The player proposing, or who made the proposal being voted on
+    pub proposer: Option<String>,
+    /// The players on the proposal being made or voted on
+    pub current_proposal: HashSet<String>,
+    /// Every proposal made so far, with how it was voted on
+    pub proposals: Vec<ProposalSummary>,
+    /// Every mission that has gone so far
+    pub missions: Vec<MissionSummary>,
+    /// The number of proposals made so far, excluding mission 1 and sent proposals
+    pub proposals_made: usize,
+    /// The maximum number of unsent proposals before force activates
+    pub max_proposals: usize,
+    /// Whether the next proposal will be sent without a vote
+    pub force: bool,
+    pub arthur: ArthurStatus,
+    /// Maeve's ability, only known to Maeve
+    pub maeve: Option<MaeveStatus>,
+    /// Players the game is waiting on in the current phase
+    pub pending: HashSet<String>,
+    /// Assassination targets suggested by Evil players, only known to Evil
+    pub nominations: Vec<Nomination>,
+    /// What the player is allowed to do right now
+    pub legal_actions: Vec<LegalAction>,
+    /// Every message sent to the player, except for progress updates like [`Message::VoteReceived`] that the
+    /// structured state already covers.
     pub log: Vec<Message>,
 }
 
+/// The phase of the game, as far as players can tell.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+pub enum SnapshotPhase {
+    /// Roles have been handed out, but no one is proposing yet
+    Starting,
+    Proposing,
+    Voting,
+    OnMission,
+    /// A mission has finished. The game may be waiting on Agravaine to declare.
+    MissionComplete,
+    Assassination,
+    Done,
+}
+
+/// A proposal and how it was voted on
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalSummary {
+    pub mission: MissionNumber,
+    pub proposer: String,
+    pub players: HashSet<String>,
+    /// The voting results, if the proposal has been voted on. On mission 1, the single vote between both proposals is
+    /// recorded on the second one.
+    pub votes: Option<VoteCounts>,
+    /// How many votes each player cast on the proposal, once it's been voted on
+    pub weights: HashMap<String, u32>,
+    /// Whether the proposal went on a mission
+    pub sent: bool,
+}
+
+/// The results of a mission
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissionSummary {
+    pub mission: MissionNumber,
+    pub players: HashSet<String>,
+    pub successes: usize,
+    pub fails: usize,
+    pub reverses: usize,
+    pub questing_beasts: usize,
+    pub passed: bool,
+    /// The player who declared as Agravaine to fail the mission, if anyone did
+    pub agravaine: Option<String>,
+}
+
+/// Arthur's ability
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArthurStatus {
+    /// Whether Arthur can declare right now. This is only ever true for Arthur.
+    pub can_declare: bool,
+    /// The player who declared as Arthur, if anyone has
+    pub declared: Option<String>,
+}
+
+/// Maeve's ability
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaeveStatus {
+    pub can_obscure: bool,
+    pub obscures_remaining: usize,
+}
+
+/// Snapshot of game state for a spectator. Spectators only see messages broadcast to every player.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpectatorSnapshot {
+    pub log: Vec<Message>,
+    /// Every player's snapshot. This is only filled in for omniscient spectators once the game is over.
+    pub players: HashMap<String, GameSnapshot>,
+}
+
 impl GameSnapshot {
     pub fn new(player: String) -> GameSnapshot {
         GameSnapshot {
             me: player,
             role_info: None,
+            paused: false,
+            phase: SnapshotPhase::Starting,
+            proposal_order: Vec::new(),
+            mission: 1,
+            mission_size: 0,
+            proposer: None,
+            current_proposal: HashSet::new(),
+            proposals: Vec::new(),
+            missions: Vec::new(),
+            proposals_made: 0,
+            max_proposals: 0,
+            force: false,
+            arthur: ArthurStatus::default(),
+            maeve: None,
+            pending: HashSet::new(),
+            nominations: Vec::new(),
+            legal_actions: Vec::new(),
             log: Vec::new(),
         }
     }
@@ -35,23 +159,226 @@ impl GameSnapshot {
     /// If the message cannot be reconciled with the current snapshot, this returns a [`SnapshotError`]. This should
     /// never happen.
     pub fn on_message(&mut self, message: Message) -> Result<(), SnapshotError> {
-        self.log.push(message.clone());
+        if !matches!(
+            message,
+            Message::VoteReceived { .. }
+                | Message::CardPlayed { .. }
+                | Message::LegalActions { .. }
+        ) {
+            self.log.push(message.clone());
+        }
 
         match message {
+            Message::ProposalOrder(order) => {
+                self.proposal_order = order;
+            }
+
             Message::RoleInformation { details } => {
                 self.role_info = Some(details);
-                Ok(())
             }
 
-            _ => Ok(()), // Some messages don't require a state update
+            Message::NextProposal {
+                proposer,
+                mission,
+                proposals_made,
+                max_proposals,
+                mission_size,
+            } => {
+                // A new proposal while still voting means Arthur declared and the vote was cancelled
+                if self.phase == SnapshotPhase::Voting
+                    && matches!(self.proposals.last(), Some(proposal) if proposal.votes.is_none())
+                {
+                    self.proposals.pop();
+                }
+                self.phase = SnapshotPhase::Proposing;
+                self.mission = mission;
+                self.mission_size = mission_size;
+                self.proposals_made = proposals_made;
+                self.max_proposals = max_proposals;
+                self.force = proposals_made >= max_proposals;
+                self.current_proposal.clear();
+                self.pending = vec![proposer.clone()].into_iter().collect();
+                self.proposer = Some(proposer);
+            }
+
+            Message::ProposalUpdated { players } => {
+                self.current_proposal = players;
+            }
+
+            Message::ProposalMade {
+                proposer,
+                mission,
+                players,
+            } => {
+                self.current_proposal = players.clone();
+                self.proposals.push(ProposalSummary {
+                    mission,
+                    proposer,
+                    players,
+                    votes: None,
+                    weights: HashMap::new(),
+                    sent: false,
+                });
+            }
+
+            Message::CommenceVoting => {
+                self.phase = SnapshotPhase::Voting;
+                self.pending = self.proposal_order.iter().cloned().collect();
+            }
+
+            Message::VoteReceived { player } => {
+                self.pending.remove(&player);
+            }
+
+            Message::VotingResults {
+                sent,
+                counts,
+                weights,
+            } => match self.proposals.last_mut() {
+                Some(proposal) => {
+                    proposal.votes = Some(counts);
+                    proposal.weights = weights;
+                    self.pending.clear();
+                }
+                None => {
+                    return Err(SnapshotError::UnexpectedMessage(Box::new(
+                        Message::VotingResults {
+                            sent,
+                            counts,
+                            weights,
+                        },
+                    )))
+                }
+            },
+
+            Message::MissionGoing { mission, players } => {
+                self.phase = SnapshotPhase::OnMission;
+                // Forced proposals go without a vote, so the mission going is what marks a proposal as sent
+                if let Some(proposal) = self
+                    .proposals
+                    .iter_mut()
+                    .rev()
+                    .find(|proposal| proposal.mission == mission && proposal.players == players)
+                {
+                    proposal.sent = true;
+                }
+                self.current_proposal = players.clone();
+                self.pending = players;
+            }
+
+            Message::CardPlayed { player } => {
+                self.pending.remove(&player);
+            }
+
+            Message::MissionResults {
+                mission,
+                successes,
+                fails,
+                reverses,
+                questing_beasts,
+                passed,
+            } => {
+                self.phase = SnapshotPhase::MissionComplete;
+                self.pending.clear();
+                self.missions.push(MissionSummary {
+                    mission,
+                    players: self.current_proposal.clone(),
+                    successes,
+                    fails,
+                    reverses,
+                    questing_beasts,
+                    passed,
+                    agravaine: None,
+                });
+            }
+
+            Message::LoversRevealed { lover } => match self.role_info.as_mut() {
+                Some(role_info) => role_info.reveal(&lover),
+                None => {
+                    return Err(SnapshotError::UnexpectedMessage(Box::new(
+                        Message::LoversRevealed { lover },
+                    )))
+                }
+            },
+
+            Message::AgravaineDeclaration { mission, player } => {
+                match self
+                    .missions
+                    .iter_mut()
+                    .find(|results| results.mission == mission)
+                {
+                    Some(results) => {
+                        results.passed = false;
+                        results.agravaine = Some(player);
+                    }
+                    None => {
+                        return Err(SnapshotError::UnexpectedMessage(Box::new(
+                            Message::AgravaineDeclaration { mission, player },
+                        )))
+                    }
+                }
+            }
+
+            Message::BeginAssassination { assassin, .. } => {
+                self.phase = SnapshotPhase::Assassination;
+                self.proposer = None;
+                self.current_proposal.clear();
+                self.pending = vec![assassin].into_iter().collect();
+            }
+
+            Message::Nomination(nomination) => {
+                self.nominations.push(nomination);
+            }
+
+            Message::GameOver { .. } => {
+                self.phase = SnapshotPhase::Done;
+                self.pending.clear();
+            }
+
+            Message::ArthurCanDeclare => {
+                self.arthur.can_declare = true;
+            }
+
+            Message::ArthurCannotDeclare => {
+                self.arthur.can_declare = false;
+            }
+
+            Message::ArthurDeclaration { player } => {
+                self.arthur.declared = Some(player);
+            }
+
+            Message::LegalActions { actions } => {
+                self.legal_actions = actions;
+            }
+
+            Message::ObscureStatus {
+                can_obscure,
+                obscures_remaining,
+            } => {
+                self.maeve = Some(MaeveStatus {
+                    can_obscure,
+                    obscures_remaining,
+                });
+            }
+
+            Message::GamePaused => {
+                self.paused = true;
+            }
+
+            Message::GameResumed => {
+                self.paused = false;
+            }
+
+            _ => (), // Some messages don't require a state update
         }
+        Ok(())
     }
 }
 
 #[derive(Error, Debug)]
 pub enum SnapshotError {
     #[error("Unexpected message: {0:?}")]
-    UnexpectedMessage(Message),
+    UnexpectedMessage(Box<Message>),
 
     #[error("No such player: {0}")]
     NoSuchPlayer(String),
@@ -63,16 +390,21 @@ impl From<SnapshotError> for GameError {
     }
 }
 
-/// An [`Interactions`] wrapper which snapshots all messages in addition to forwarding them to another [`Interactions`]
+/// An [`Interactions`] wrapper which snapshots all messages in addition to forwarding them to another [`Interactions`].
+/// Broadcasts are also published to spectators.
 pub struct SnapshotInteractions<I: Interactions> {
     inner: I,
     snapshots: Arc<Mutex<HashMap<String, Arc<Mutex<GameSnapshot>>>>>,
+    public: Arc<Mutex<Vec<Message>>>,
+    spectators: broadcast::Sender<Message>,
 }
 
 /// Handle to the per-player snapshots maintained by [`SnapshotInteractions`].
 #[derive(Debug, Clone)]
 pub struct Snapshots {
     inner: Arc<Mutex<HashMap<String, Arc<Mutex<GameSnapshot>>>>>,
+    public: Arc<Mutex<Vec<Message>>>,
+    spectators: broadcast::Sender<Message>,
 }
 
 impl<I: Interactions> SnapshotInteractions<I> {
@@ -85,10 +417,14 @@ impl<I: Interactions> SnapshotInteractions<I> {
                 (player, snapshot)
             })
             .collect();
+        // Spectators that fall this far behind will miss messages, but can catch up from their snapshot.
+        let (spectators, _) = broadcast::channel(32);
 
         SnapshotInteractions {
             inner,
             snapshots: Arc::new(Mutex::new(snapshots)),
+            public: Arc::new(Mutex::new(Vec::new())),
+            spectators,
         }
     }
 
@@ -96,6 +432,8 @@ impl<I: Interactions> SnapshotInteractions<I> {
     pub fn snapshots(&self) -> Snapshots {
         Snapshots {
             inner: self.snapshots.clone(),
+            public: self.public.clone(),
+            spectators: self.spectators.clone(),
         }
     }
 
@@ -103,6 +441,17 @@ impl<I: Interactions> SnapshotInteractions<I> {
         let snapshots = self.snapshots.lock().unwrap();
         snapshots.get(player).cloned()
     }
+
+    /// Updates every player's snapshot and the public log with a message sent to all players.
+    fn on_broadcast(&mut self, message: &Message) -> Result<(), GameError> {
+        let snapshots = self.snapshots.lock().unwrap();
+        for snapshot in snapshots.values() {
+            let mut snapshot = snapshot.lock().unwrap();
+            snapshot.on_message(message.clone())?;
+        }
+        self.public.lock().unwrap().push(message.clone());
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -119,19 +468,29 @@ impl<I: Interactions + Send> Interactions for SnapshotInteractions<I> {
     }
 
     async fn send(&mut self, message: Message) -> Result<(), GameError> {
-        {
-            let snapshots = self.snapshots.lock().unwrap();
-            for snapshot in snapshots.values() {
-                let mut snapshot = snapshot.lock().unwrap();
-                snapshot.on_message(message.clone())?;
-            }
-        }
+        self.on_broadcast(&message)?;
+        // Sending only fails if there are no spectators, which is fine.
+        let _ = self.spectators.send(message.clone());
         self.inner.send(message).await
     }
 
     async fn receive(&mut self) -> Result<(String, Action), GameError> {
         self.inner.receive().await
     }
+
+    async fn restore(&mut self, player: Option<&str>, message: Message) -> Result<(), GameError> {
+        match player {
+            Some(player) => {
+                let snapshot = self
+                    .snapshot(player)
+                    .ok_or_else(|| SnapshotError::NoSuchPlayer(player.to_string()))?;
+                let mut snapshot = snapshot.lock().unwrap();
+                snapshot.on_message(message)?;
+            }
+            None => self.on_broadcast(&message)?,
+        }
+        Ok(())
+    }
 }
 
 impl Snapshots {
@@ -141,4 +500,205 @@ impl Snapshots {
         let snapshots = self.inner.lock().unwrap();
         snapshots.get(player).cloned()
     }
+
+    /// Subscribes to messages broadcast to all players, which is everything a spectator may see. Messages sent
+    /// before subscribing are only available through [`Snapshots::spectator_snapshot`].
+    pub fn spectate(&self) -> broadcast::Receiver<Message> {
+        self.spectators.subscribe()
+    }
+
+    /// Gets a snapshot of the game for spectators. If `omniscient` is set and the game is over, this includes every
+    /// player's snapshot.
+    pub fn spectator_snapshot(&self, omniscient: bool) -> SpectatorSnapshot {
+        let log = self.public.lock().unwrap().clone();
+        let game_over = log
+            .iter()
+            .any(|message| matches!(message, Message::GameOver { .. }));
+        let players = if omniscient && game_over {
+            let snapshots = self.inner.lock().unwrap();
+            snapshots
+                .iter()
+                .map(|(player, snapshot)| (player.clone(), snapshot.lock().unwrap().clone()))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        SpectatorSnapshot { log, players }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use super::super::interactions::{test::TestInteractions, Interactions};
+    use super::super::messages::{Message, VoteCounts};
+    use super::super::role::Team;
+    use super::{GameSnapshot, SnapshotInteractions, SnapshotPhase};
+
+    fn players(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_structured_state() {
+        let mut snapshot = GameSnapshot::new("Alice".to_string());
+        let messages = vec![
+            Message::ProposalOrder(vec!["Alice".into(), "Bob".into(), "Carol".into()]),
+            Message::NextProposal {
+                proposer: "Bob".into(),
+                mission: 2,
+                proposals_made: 0,
+                max_proposals: 1,
+                mission_size: 2,
+            },
+            Message::ProposalUpdated {
+                players: players(&["Bob"]),
+            },
+        ];
+        for message in messages {
+            snapshot.on_message(message).unwrap();
+        }
+        assert_eq!(snapshot.phase, SnapshotPhase::Proposing);
+        assert_eq!(snapshot.proposer.as_deref(), Some("Bob"));
+        assert_eq!(snapshot.current_proposal, players(&["Bob"]));
+        assert_eq!(snapshot.pending, players(&["Bob"]));
+        assert!(!snapshot.force);
+
+        let messages = vec![
+            Message::ProposalMade {
+                proposer: "Bob".into(),
+                mission: 2,
+                players: players(&["Alice", "Bob"]),
+            },
+            Message::CommenceVoting,
+            Message::VoteReceived {
+                player: "Carol".into(),
+            },
+        ];
+        for message in messages {
+            snapshot.on_message(message).unwrap();
+        }
+        assert_eq!(snapshot.phase, SnapshotPhase::Voting);
+        assert_eq!(snapshot.pending, players(&["Alice", "Bob"]));
+
+        let counts = VoteCounts::Obscured {
+            upvotes: 1,
+            downvotes: 2,
+        };
+        let messages = vec![
+            Message::VotingResults {
+                sent: false,
+                counts: counts.clone(),
+                weights: HashMap::new(),
+            },
+            Message::NextProposal {
+                proposer: "Carol".into(),
+                mission: 2,
+                proposals_made: 1,
+                max_proposals: 1,
+                mission_size: 2,
+            },
+            Message::ProposalMade {
+                proposer: "Carol".into(),
+                mission: 2,
+                players: players(&["Alice", "Carol"]),
+            },
+        ];
+        for message in messages {
+            snapshot.on_message(message).unwrap();
+        }
+        assert!(snapshot.force);
+        assert_eq!(snapshot.proposals.len(), 2);
+        assert_eq!(snapshot.proposals[0].votes, Some(counts));
+        assert!(!snapshot.proposals[0].sent);
+
+        // The second proposal is forced, so it goes without a vote
+        let messages = vec![
+            Message::MissionGoing {
+                mission: 2,
+                players: players(&["Alice", "Carol"]),
+            },
+            Message::CardPlayed {
+                player: "Carol".into(),
+            },
+        ];
+        for message in messages {
+            snapshot.on_message(message).unwrap();
+        }
+        assert_eq!(snapshot.phase, SnapshotPhase::OnMission);
+        assert!(snapshot.proposals[1].sent);
+        assert_eq!(snapshot.proposals[1].votes, None);
+        assert_eq!(snapshot.pending, players(&["Alice"]));
+
+        let messages = vec![
+            Message::MissionResults {
+                mission: 2,
+                successes: 1,
+                fails: 1,
+                reverses: 0,
+                questing_beasts: 0,
+                passed: true,
+            },
+            Message::AgravaineDeclaration {
+                mission: 2,
+                player: "Carol".into(),
+            },
+        ];
+        for message in messages {
+            snapshot.on_message(message).unwrap();
+        }
+        assert_eq!(snapshot.phase, SnapshotPhase::MissionComplete);
+        assert!(snapshot.pending.is_empty());
+        assert_eq!(snapshot.missions.len(), 1);
+        assert!(!snapshot.missions[0].passed);
+        assert_eq!(snapshot.missions[0].agravaine.as_deref(), Some("Carol"));
+        assert_eq!(snapshot.missions[0].players, players(&["Alice", "Carol"]));
+
+        // Progress updates are left out of the log
+        assert!(!snapshot.log.iter().any(|message| matches!(
+            message,
+            Message::VoteReceived { .. } | Message::CardPlayed { .. }
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_spectators_see_public_messages() {
+        let players = vec!["Alice".to_string(), "Bob".to_string()];
+        let mut interactions = SnapshotInteractions::new(TestInteractions::new(), players);
+        let snapshots = interactions.snapshots();
+        let mut spectator = snapshots.spectate();
+
+        interactions
+            .send_to("Alice", Message::ArthurCanDeclare)
+            .await
+            .unwrap();
+        interactions.send(Message::CommenceVoting).await.unwrap();
+
+        assert_eq!(spectator.try_recv().unwrap(), Message::CommenceVoting);
+        assert!(spectator.try_recv().is_err());
+        let snapshot = snapshots.spectator_snapshot(true);
+        assert_eq!(snapshot.log, vec![Message::CommenceVoting]);
+        assert!(snapshot.players.is_empty());
+
+        let game_over = Message::GameOver {
+            winning_team: Team::Good,
+            roles: HashMap::new(),
+            seed: 0,
+        };
+        interactions.send(game_over.clone()).await.unwrap();
+        assert_eq!(spectator.try_recv().unwrap(), game_over);
+        assert!(snapshots.spectator_snapshot(false).players.is_empty());
+
+        let omniscient = snapshots.spectator_snapshot(true);
+        assert_eq!(omniscient.players.len(), 2);
+        assert_eq!(
+            omniscient.players["Alice"].log,
+            vec![
+                Message::ArthurCanDeclare,
+                Message::CommenceVoting,
+                game_over
+            ]
+        );
+    }
 }
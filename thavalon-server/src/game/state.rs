@@ -1,11 +1,14 @@
 #![allow(dead_code)]
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::time::Duration;
 
-use super::messages::{Action, Message};
+use super::messages::{
+    Action, AssassinationRecord, GameActionError, GameReveal, LegalAction, Message, MissionRecord,
+    ProposalRecord,
+};
 use super::role::{Role, Team};
-use super::{Game, MissionNumber};
+use super::{Card, Game, MissionNumber};
 
 use self::assassination::Assassination;
 use self::on_mission::{OnMission, WaitingForAgravaine};
@@ -47,6 +50,7 @@ pub struct GameState<P: Phase> {
 /// Phase used when the game is over.
 pub struct Done {
     winning_team: Team,
+    reveal: GameReveal,
 }
 
 /// A phase of the THavalon state machine
@@ -81,15 +85,14 @@ mod prelude {
         ActionResult, Done, Effect, GameState, GameStateWrapper, MissionResults, Phase, Proposal,
     };
 
-    pub use super::assassination::Assassination;
     pub use super::on_mission::OnMission;
     pub use super::proposing::Proposing;
     pub use super::voting::Voting;
 
     pub use super::super::{
-        messages::{self, Action, Message, ToastSeverity},
+        messages::{self, GameActionError, Message, ToastSeverity},
         role::{PriorityTarget, Role, Team},
-        Card, Game, GameSpec,
+        Card, Game, GameSpec, MissionNumber,
     };
 }
 
@@ -104,20 +107,32 @@ pub enum Effect {
 }
 
 pub struct Proposal {
+    mission: MissionNumber,
     proposer: String,
     players: HashSet<String>,
+    /// How each player voted on the proposal, once voting is over
+    votes: Option<HashMap<String, bool>>,
+    obscured: bool,
 }
 
 pub struct MissionResults {
     passed: bool,
     players: HashSet<String>,
+    /// The proposal the mission was based on
+    proposal: usize,
+    cards: HashMap<String, Card>,
+    questing_beasts: usize,
+    /// Whether Agravaine had a chance to declare after the mission
+    agravaine_window: bool,
+    /// The player who declared as Agravaine, if anyone did
+    agravaine_declaration: Option<String>,
 }
 
 // Convenience methods shared across game phases
 impl<P: Phase> GameState<P> {
     /// Generate an [`ActionResult`] that keeps the current state and returns an error reply to the player.
-    fn player_error<S: Into<String>>(self, message: S) -> ActionResult {
-        (P::wrap(self), vec![player_error(message)])
+    fn player_error(self, error: GameActionError) -> ActionResult {
+        (P::wrap(self), vec![player_error(error)])
     }
 
     /// The current mission, indexed starting at 1
@@ -156,7 +171,7 @@ impl<P: Phase> GameState<P> {
                 ],
             )
         } else {
-            self.player_error("You cannot declare as Arthur right now")
+            self.player_error(GameActionError::CannotDeclare)
         }
     }
 
@@ -171,6 +186,19 @@ impl<P: Phase> GameState<P> {
         }
     }
 
+    /// Starts the turn timer for the phase being entered, if it has a time limit, and announces it to players.
+    /// Otherwise, this clears any timer left over from the previous phase.
+    fn set_turn_timer(&self, timer: Option<Duration>, effects: &mut Vec<Effect>) {
+        if let Some(duration) = timer {
+            effects.push(Effect::StartTimeout(duration));
+            effects.push(Effect::Broadcast(Message::TurnTimer {
+                seconds: duration.as_secs(),
+            }));
+        } else if self.game.spec.turn_timers.is_enabled() {
+            effects.push(Effect::ClearTimeout);
+        }
+    }
+
     /// Switch into the `Proposing` state with `proposer` as the next player to propose. In addition to effects
     /// related to the next proposal, the returned [`ActionResult`] will include `effects`.
     fn into_proposing(self, proposer: String, mut effects: Vec<Effect>) -> ActionResult {
@@ -181,20 +209,79 @@ impl<P: Phase> GameState<P> {
             max_proposals: self.game.spec.max_proposals,
             mission_size: self.game.spec.mission_size(self.mission()),
         }));
+        self.set_turn_timer(self.game.spec.turn_timers.proposal, &mut effects);
         let next_state = self.with_phase(Proposing::new(proposer));
         (GameStateWrapper::Proposing(next_state), effects)
     }
 
-    /// Switch into the `Done` state with `winning_team` as the winners. The returned [`ActionResult`]
-    /// will include `effects`.
-    fn into_done(self, winning_team: Team, mut effects: Vec<Effect>) -> ActionResult {
+    /// Switch into the `Done` state with `winning_team` as the winners, revealing the full game history including
+    /// `assassination`, if there was one. The returned [`ActionResult`] will include `effects`.
+    fn into_done(
+        self,
+        winning_team: Team,
+        assassination: Option<AssassinationRecord>,
+        mut effects: Vec<Effect>,
+    ) -> ActionResult {
         effects.push(Effect::Broadcast(Message::GameOver {
             winning_team,
             roles: self.game.info.clone(),
+            seed: self.game.seed(),
         }));
-        let next_state = self.with_phase(Done::new(winning_team));
+        let reveal = self.reveal(winning_team, assassination);
+        effects.push(Effect::Broadcast(Message::GameReveal(reveal.clone())));
+        let next_state = self.with_phase(Done::new(winning_team, reveal));
         (GameStateWrapper::Done(next_state), effects)
     }
+
+    /// Builds the record of everything that happened in the game, for revealing once it's over.
+    fn reveal(&self, winning_team: Team, assassination: Option<AssassinationRecord>) -> GameReveal {
+        let proposals = self
+            .proposals
+            .iter()
+            .enumerate()
+            .map(|(index, proposal)| ProposalRecord {
+                mission: proposal.mission,
+                proposer: proposal.proposer.clone(),
+                players: proposal.players.clone(),
+                votes: proposal.votes.clone(),
+                obscured: proposal.obscured,
+                sent: self
+                    .mission_results
+                    .iter()
+                    .any(|mission| mission.proposal == index),
+            })
+            .collect();
+
+        let missions = self
+            .mission_results
+            .iter()
+            .enumerate()
+            .map(|(index, mission)| MissionRecord {
+                mission: index as MissionNumber + 1,
+                proposal: mission.proposal,
+                cards: mission.cards.clone(),
+                questing_beasts: mission.questing_beasts,
+                passed: mission.passed,
+                agravaine_window: mission.agravaine_window,
+                agravaine_declaration: mission.agravaine_declaration.clone(),
+            })
+            .collect();
+
+        GameReveal {
+            winning_team,
+            roles: self
+                .game
+                .players
+                .iter()
+                .map(|player| (player.name.clone(), player.role))
+                .collect(),
+            assassin: self.game.assassin.clone(),
+            priority_target: self.game.priority_target,
+            proposals,
+            missions,
+            assassination,
+        }
+    }
 }
 
 /// Macro for repeating identical code across phases, with a fallback for any other phases.
@@ -265,14 +352,18 @@ impl GameStateWrapper {
             mission_results: vec![],
         };
         RoleState::on_round_start(&mut state, &mut effects);
+        state.set_turn_timer(state.game.spec.turn_timers.proposal, &mut effects);
 
-        (GameStateWrapper::Proposing(state), effects)
+        let state = GameStateWrapper::Proposing(state);
+        state.push_legal_actions(&HashMap::new(), &mut effects);
+        (state, effects)
     }
 
     /// Advance to the next game state given a player action
     pub fn handle_action(self, player: &str, action: Action) -> ActionResult {
         log::debug!("Responding to {:?} from {}", action, player);
-        match (self, action) {
+        let before = self.all_legal_actions();
+        let (state, mut effects) = match (self, action) {
             (GameStateWrapper::Proposing(inner), Action::Propose { players }) => {
                 inner.handle_proposal(player, players)
             }
@@ -295,9 +386,30 @@ impl GameStateWrapper {
             (GameStateWrapper::WaitingForAgravaine(inner), Action::Declare) => {
                 inner.handle_declaration(player)
             }
-            (GameStateWrapper::Assassination(inner), Action::Assassinate { target, players }) => {
-                inner.handle_assassination(player, target, players)
+            (GameStateWrapper::Proposing(inner), Action::Declare)
+                if inner.game.players.is(player, Role::Agravaine) =>
+            {
+                inner.handle_agravaine_declaration(player)
             }
+            (
+                GameStateWrapper::Assassination(inner),
+                Action::Assassinate {
+                    target,
+                    players,
+                    other,
+                },
+            ) => inner.handle_assassination(player, target, players, other),
+            (GameStateWrapper::Assassination(inner), Action::Discuss { message }) => {
+                inner.handle_discussion(player, message)
+            }
+            (
+                GameStateWrapper::Assassination(inner),
+                Action::Nominate {
+                    target,
+                    players,
+                    other,
+                },
+            ) => inner.handle_nomination(player, target, players, other),
 
             (state, Action::Declare) if state.game().players.is(player, Role::Arthur) => {
                 let (state, effects) =
@@ -310,25 +422,48 @@ impl GameStateWrapper {
 
             (state, Action::MoveToAssassination) => in_phases!(state,
                 Proposing | Voting | OnMission | WaitingForAgravaine => |inner| inner.move_to_assassinate(player),
-                |state| => (state, vec![player_error("You can't move to assassination right now")])
+                |state| => (state, vec![player_error(GameActionError::CannotMoveToAssassination)])
             ),
 
-            (state, _) => (state, vec![player_error("You can't do that right now")]),
-        }
+            (state, _) => (state, vec![player_error(GameActionError::WrongPhase)]),
+        };
+
+        state.push_legal_actions(&before, &mut effects);
+        (state, effects)
     }
 
-    /// Handles a timeout set by [`Effect::SetTimeout`] expiring. This is used for player actions which must happen in a
-    /// certain time window, like Agravaine declarations.
+    /// Handles a timeout set by [`Effect::StartTimeout`] expiring. This is used for player actions which must happen in
+    /// a certain time window, like Agravaine declarations and turns with a turn timer.
     pub fn handle_timeout(self) -> ActionResult {
         log::debug!("Action timeout expired");
-        match self {
+        let before = self.all_legal_actions();
+        let (state, mut effects) = match self {
+            GameStateWrapper::Proposing(inner)
+                if inner.game.spec.turn_timers.proposal.is_some() =>
+            {
+                inner.handle_timeout()
+            }
+            GameStateWrapper::Voting(inner) if inner.game.spec.turn_timers.voting.is_some() => {
+                inner.handle_timeout()
+            }
+            GameStateWrapper::OnMission(inner) if inner.game.spec.turn_timers.mission.is_some() => {
+                inner.handle_timeout()
+            }
             GameStateWrapper::WaitingForAgravaine(inner) => inner.handle_timeout(),
+            GameStateWrapper::Assassination(inner)
+                if inner.phase.is_discussing()
+                    && inner.game.spec.turn_timers.discussion.is_some() =>
+            {
+                inner.handle_timeout()
+            }
             _ => {
                 // This might happen if we transition to a new phase (like assassination) while a timeout is active.
                 log::warn!("Timeout expired when no timeout should have been set");
-                (self, vec![])
+                return (self, vec![]);
             }
-        }
+        };
+        state.push_legal_actions(&before, &mut effects);
+        (state, effects)
     }
 
     /// Returns whether or not the game is over
@@ -336,14 +471,93 @@ impl GameStateWrapper {
         matches!(self, GameStateWrapper::Done(_))
     }
 
+    /// The full history of the game, once it's over.
+    pub fn reveal(&self) -> Option<&GameReveal> {
+        match self {
+            GameStateWrapper::Done(inner) => Some(&inner.phase.reveal),
+            _ => None,
+        }
+    }
+
+    /// The actions `player` is allowed to take in the current phase of the game. Once the game is over, there are
+    /// none.
+    pub fn legal_actions(&self, player: &str) -> Vec<LegalAction> {
+        let mut actions = match self {
+            GameStateWrapper::Proposing(inner) => inner.legal_actions(player),
+            GameStateWrapper::Voting(inner) => inner.legal_actions(player),
+            GameStateWrapper::OnMission(inner) => inner.legal_actions(player),
+            GameStateWrapper::WaitingForAgravaine(inner) => inner.legal_actions(player),
+            GameStateWrapper::Assassination(inner) => inner.legal_actions(player),
+            GameStateWrapper::Done(_) => return Vec::new(),
+        };
+
+        // Arthur can declare in any phase, which is handled outside of the individual phases
+        let arthur_can_declare =
+            any_phase!(self, |inner| inner.game.players.is(player, Role::Arthur)
+                && inner.role_state.arthur.can_declare(inner));
+        if arthur_can_declare {
+            actions.push(LegalAction::Declare);
+        }
+
+        let can_move = in_phases!(self,
+            Proposing | Voting | OnMission | WaitingForAgravaine => |inner| player == inner.game.assassin,
+            |_| => false
+        );
+        if can_move {
+            actions.push(LegalAction::MoveToAssassination);
+        }
+        actions
+    }
+
+    /// The actions each player is allowed to take, by player name.
+    fn all_legal_actions(&self) -> HashMap<String, Vec<LegalAction>> {
+        self.game()
+            .players
+            .iter()
+            .map(|player| (player.name.clone(), self.legal_actions(&player.name)))
+            .collect()
+    }
+
+    /// Sends players the actions they're allowed to take, so clients don't need to know the rules themselves. Only
+    /// players whose actions changed from `before` are sent them, so an empty `before` sends every player theirs.
+    pub fn push_legal_actions(
+        &self,
+        before: &HashMap<String, Vec<LegalAction>>,
+        effects: &mut Vec<Effect>,
+    ) {
+        for (player, actions) in self.all_legal_actions() {
+            if before.get(&player) != Some(&actions) {
+                effects.push(Effect::Send(player, Message::LegalActions { actions }));
+            }
+        }
+    }
+
+    /// Tells every player they can't do anything right now, for example because the game is paused.
+    pub fn clear_legal_actions(&self, effects: &mut Vec<Effect>) {
+        for player in self.game().players.iter() {
+            effects.push(Effect::Send(
+                player.name.clone(),
+                Message::LegalActions {
+                    actions: Vec::new(),
+                },
+            ));
+        }
+    }
+
     fn game(&self) -> &Game {
-        any_phase!(self, |inner| &inner.game)
+        match self {
+            GameStateWrapper::Done(inner) => &inner.game,
+            _ => any_phase!(self, |inner| &inner.game),
+        }
     }
 }
 
 impl Done {
-    pub fn new(winning_team: Team) -> Done {
-        Done { winning_team }
+    pub fn new(winning_team: Team, reveal: GameReveal) -> Done {
+        Done {
+            winning_team,
+            reveal,
+        }
     }
 }
 
@@ -360,6 +574,1198 @@ impl fmt::Display for Proposal {
 }
 
 /// Generate an [`Effect`] that sends an error reply to the player.
-fn player_error<S: Into<String>>(message: S) -> Effect {
-    Effect::Reply(Message::Error(message.into()))
+fn player_error(error: GameActionError) -> Effect {
+    Effect::Reply(Message::Error(error))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    use super::super::messages::{Accusation, AssassinationOptions, VoteCounts};
+    use super::super::role::PriorityTarget;
+    use super::super::{Card, Ruleset};
+    use super::*;
+
+    fn has_timer(effects: &[Effect], seconds: u64) -> bool {
+        let started = effects.iter().any(|effect| {
+            matches!(effect, Effect::StartTimeout(duration) if *duration == Duration::from_secs(seconds))
+        });
+        let announced = effects.iter().any(|effect| {
+            matches!(effect, Effect::Broadcast(Message::TurnTimer { seconds: s }) if *s == seconds)
+        });
+        started && announced
+    }
+
+    fn proposal(players: &[&str]) -> HashSet<String> {
+        players.iter().map(|player| player.to_string()).collect()
+    }
+
+    /// Starts a game with the default rules where each player has the given role, returning the starting state and
+    /// effects along with the proposal order.
+    fn start_game(roles: &[(&str, Role)]) -> (GameStateWrapper, Vec<Effect>, Vec<String>) {
+        let roles = roles
+            .iter()
+            .map(|(player, role)| (player.to_string(), *role))
+            .collect();
+        let game = Game::with_roles(roles, &Ruleset::default(), 0).unwrap();
+        let order = game.proposal_order().to_vec();
+        let (state, effects) = GameStateWrapper::new(game);
+        (state, effects, order)
+    }
+
+    /// Makes both mission 1 proposals with `players` on them, and has everyone upvote to send the first one.
+    fn send_first_mission(
+        mut state: GameStateWrapper,
+        order: &[String],
+        players: &[&str],
+    ) -> GameStateWrapper {
+        for proposer in order[..2].iter() {
+            let (next_state, _) = state.handle_action(
+                proposer,
+                Action::Propose {
+                    players: proposal(players),
+                },
+            );
+            state = next_state;
+        }
+        for player in order.iter() {
+            let (next_state, _) = state.handle_action(player, Action::Vote { upvote: true });
+            state = next_state;
+        }
+        state
+    }
+
+    /// Plays `cards` on the mission that's going, returning the effects of the last card played.
+    fn play_cards(mut state: GameStateWrapper, cards: &[(&str, Card)]) -> ActionResult {
+        let mut effects = vec![];
+        for (player, card) in cards {
+            let (next_state, next_effects) =
+                state.handle_action(player, Action::Play { card: *card });
+            state = next_state;
+            effects = next_effects;
+        }
+        (state, effects)
+    }
+
+    /// Sends mission 1 with the players in `cards`, who then play their cards.
+    fn play_first_mission(
+        state: GameStateWrapper,
+        order: &[String],
+        cards: &[(&str, Card)],
+    ) -> ActionResult {
+        let players = cards.iter().map(|(player, _)| *player).collect::<Vec<_>>();
+        play_cards(send_first_mission(state, order, &players), cards)
+    }
+
+    #[test]
+    fn test_turn_timers() {
+        let ruleset = Ruleset {
+            proposal_timer: Some(60),
+            voting_timer: Some(30),
+            mission_timer: Some(45),
+            ..Ruleset::default()
+        };
+        let roles = vec![
+            ("Alice".to_string(), Role::Merlin),
+            ("Bob".to_string(), Role::Percival),
+            ("Carol".to_string(), Role::Tristan),
+            ("Dave".to_string(), Role::Mordred),
+            ("Eve".to_string(), Role::Morgana),
+        ];
+        let game = Game::with_roles(roles, &ruleset, 0).unwrap();
+        let order = game.proposal_order().to_vec();
+
+        let (state, effects) = GameStateWrapper::new(game);
+        assert!(has_timer(&effects, 60));
+
+        // The first proposer runs out of time, so the second proposer goes instead
+        let (state, effects) = state.handle_timeout();
+        assert!(has_timer(&effects, 60));
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::NextProposal { proposer, mission: 1, .. }) if proposer == &order[1]
+        )));
+
+        let (state, _) = state.handle_action(
+            &order[1],
+            Action::Propose {
+                players: proposal(&["Alice", "Bob"]),
+            },
+        );
+        let (state, effects) = state.handle_action(
+            &order[2],
+            Action::Propose {
+                players: proposal(&["Carol", "Dave"]),
+            },
+        );
+        assert!(matches!(state, GameStateWrapper::Voting(_)));
+        assert!(has_timer(&effects, 30));
+
+        // Everyone but Alice runs out of time, so the second proposal goes
+        let (state, _) = state.handle_action("Alice", Action::Vote { upvote: true });
+        let (state, effects) = state.handle_timeout();
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::VotingResults { sent: false, .. })
+        )));
+        assert!(matches!(state, GameStateWrapper::OnMission(_)));
+        assert!(has_timer(&effects, 45));
+
+        // Carol plays, and Dave runs out of time. Dave can play a Success, so one is played for them.
+        let (state, _) = state.handle_action(
+            "Carol",
+            Action::Play {
+                card: Card::Success,
+            },
+        );
+        let (state, effects) = state.handle_timeout();
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::MissionResults {
+                successes: 2,
+                passed: true,
+                ..
+            })
+        )));
+        assert!(matches!(state, GameStateWrapper::Proposing(_)));
+        assert!(has_timer(&effects, 60));
+    }
+
+    fn next_proposer(effects: &[Effect]) -> String {
+        effects
+            .iter()
+            .find_map(|effect| match effect {
+                Effect::Broadcast(Message::NextProposal { proposer, .. }) => Some(proposer.clone()),
+                _ => None,
+            })
+            .expect("No next proposer")
+    }
+
+    #[test]
+    fn test_reveal() {
+        let (state, _, order) = start_game(&[
+            ("Alice", Role::Merlin),
+            ("Bob", Role::Percival),
+            ("Carol", Role::Tristan),
+            ("Dave", Role::Mordred),
+            ("Eve", Role::Morgana),
+        ]);
+
+        // Mission 1 gets two proposals, and everyone votes to send the first
+        let mut state = send_first_mission(state, &order, &["Dave", "Eve"]);
+
+        // Evil fails every mission from here on
+        let mut effects = vec![];
+        for (mission, players) in [
+            (1, &["Dave", "Eve"][..]),
+            (2, &["Alice", "Dave", "Eve"][..]),
+            (3, &["Dave", "Eve"][..]),
+        ] {
+            if mission != 1 {
+                let (next_state, _) = state.handle_action(
+                    &next_proposer(&effects),
+                    Action::Propose {
+                        players: proposal(players),
+                    },
+                );
+                state = next_state;
+                for player in order.iter() {
+                    let (next_state, _) =
+                        state.handle_action(player, Action::Vote { upvote: true });
+                    state = next_state;
+                }
+            }
+
+            for player in players {
+                let card = if *player == "Dave" {
+                    Card::Fail
+                } else {
+                    Card::Success
+                };
+                let (next_state, next_effects) = state.handle_action(player, Action::Play { card });
+                state = next_state;
+                effects = next_effects;
+            }
+        }
+
+        assert!(effects
+            .iter()
+            .any(|effect| matches!(effect, Effect::Broadcast(Message::GameReveal(_)))));
+
+        let reveal = state.reveal().expect("Game should be over");
+        assert_eq!(reveal.winning_team, Team::Evil);
+        assert_eq!(reveal.roles["Dave"], Role::Mordred);
+        assert_eq!(reveal.proposals.len(), 4);
+        assert!(reveal.proposals[0].sent);
+        assert!(!reveal.proposals[1].sent);
+        assert_eq!(reveal.proposals[1].votes.as_ref().unwrap().len(), 5);
+        assert!(reveal.proposals.iter().all(|proposal| !proposal.obscured));
+
+        assert_eq!(reveal.missions.len(), 3);
+        assert_eq!(reveal.missions[1].proposal, 2);
+        assert_eq!(reveal.missions[1].cards["Dave"], Card::Fail);
+        assert_eq!(reveal.missions[1].cards["Alice"], Card::Success);
+        assert!(reveal.missions.iter().all(|mission| !mission.passed));
+        assert!(reveal.assassination.is_none());
+    }
+
+    fn is_error(effects: &[Effect], expected: GameActionError) -> bool {
+        matches!(effects, [Effect::Reply(Message::Error(error))] if *error == expected)
+    }
+
+    #[test]
+    fn test_proposal_errors() {
+        let game = Game::roll_with_seed(
+            (1..=5).map(|n| format!("Player {}", n)).collect(),
+            &Ruleset::default(),
+            0,
+        )
+        .unwrap();
+        let order = game.proposal_order().to_vec();
+        let (state, _) = GameStateWrapper::new(game);
+
+        let (state, effects) = state.handle_action(
+            &order[1],
+            Action::Propose {
+                players: proposal(&[&order[0], &order[1]]),
+            },
+        );
+        assert!(is_error(&effects, GameActionError::NotProposer));
+        let (state, effects) = state.handle_action(
+            &order[0],
+            Action::Propose {
+                players: proposal(&[&order[0]]),
+            },
+        );
+        assert!(is_error(
+            &effects,
+            GameActionError::ProposalSize {
+                expected: 2,
+                actual: 1
+            }
+        ));
+        let (state, effects) = state.handle_action(
+            &order[0],
+            Action::SelectPlayer {
+                player: "Mallory".to_string(),
+            },
+        );
+        assert!(is_error(
+            &effects,
+            GameActionError::NotInGame {
+                player: "Mallory".to_string()
+            }
+        ));
+        let (_, effects) = state.handle_action(
+            &order[0],
+            Action::UnselectPlayer {
+                player: order[1].clone(),
+            },
+        );
+        assert!(is_error(
+            &effects,
+            GameActionError::NotOnProposal {
+                player: order[1].clone()
+            }
+        ));
+
+        // Errors have a stable code for clients, along with any details
+        let error = Message::Error(GameActionError::ProposalSize {
+            expected: 2,
+            actual: 1,
+        });
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "messageType": "error",
+                "data": { "code": "proposalSize", "expected": 2, "actual": 1 },
+            })
+        );
+    }
+
+    #[test]
+    fn test_questing_beast_limits() {
+        let (state, _, order) = start_game(&[
+            ("Alice", Role::Merlin),
+            ("Bob", Role::Percival),
+            ("Carol", Role::Tristan),
+            ("Dave", Role::Mordred),
+            ("Eve", Role::Morgana),
+        ]);
+        let state = send_first_mission(state, &order, &["Alice", "Dave"]);
+
+        let (state, effects) = state.handle_action("Bob", Action::QuestingBeast);
+        assert!(is_error(&effects, GameActionError::NotOnMission));
+        let (state, effects) = state.handle_action("Dave", Action::QuestingBeast);
+        assert!(is_error(&effects, GameActionError::CannotPlayQuestingBeast));
+        let (state, effects) = state.handle_action("Alice", Action::QuestingBeast);
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Send(player, Message::LegalActions { actions })
+                if player == "Alice" && !actions.contains(&LegalAction::QuestingBeast)
+        )));
+        let (state, effects) = state.handle_action("Alice", Action::QuestingBeast);
+        assert!(is_error(
+            &effects,
+            GameActionError::QuestingBeastAlreadyPlayed
+        ));
+
+        let (state, _) = state.handle_action(
+            "Alice",
+            Action::Play {
+                card: Card::Success,
+            },
+        );
+        let (state, effects) = state.handle_action(
+            "Dave",
+            Action::Play {
+                card: Card::Success,
+            },
+        );
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::MissionResults {
+                questing_beasts: 1,
+                passed: true,
+                ..
+            })
+        )));
+
+        // Each player only gets one Questing Beast per game
+        let (mut state, _) = state.handle_action(
+            &next_proposer(&effects),
+            Action::Propose {
+                players: proposal(&["Alice", "Bob", "Dave"]),
+            },
+        );
+        for player in order.iter() {
+            let (next_state, _) = state.handle_action(player, Action::Vote { upvote: true });
+            state = next_state;
+        }
+        let (state, effects) = state.handle_action("Alice", Action::QuestingBeast);
+        assert!(is_error(&effects, GameActionError::NoQuestingBeastsLeft));
+
+        let (state, _) = state.handle_action(
+            "Bob",
+            Action::Play {
+                card: Card::Success,
+            },
+        );
+        let (_, effects) = state.handle_action("Bob", Action::QuestingBeast);
+        assert!(is_error(&effects, GameActionError::QuestingBeastAfterCard));
+    }
+
+    /// Proposes `players` as the next proposer, has everyone upvote, and then plays `cards` on the mission.
+    fn send_mission(
+        state: GameStateWrapper,
+        effects: &[Effect],
+        order: &[String],
+        cards: &[(&str, Card)],
+    ) -> ActionResult {
+        let players = cards.iter().map(|(player, _)| *player).collect::<Vec<_>>();
+        let (mut state, _) = state.handle_action(
+            &next_proposer(effects),
+            Action::Propose {
+                players: proposal(&players),
+            },
+        );
+        for player in order.iter() {
+            let (next_state, _) = state.handle_action(player, Action::Vote { upvote: true });
+            state = next_state;
+        }
+        play_cards(state, cards)
+    }
+
+    #[test]
+    fn test_agravaine_window() {
+        let (state, _, order) = start_game(&[
+            ("Alice", Role::Lancelot),
+            ("Bob", Role::Percival),
+            ("Carol", Role::Merlin),
+            ("Dave", Role::Agravaine),
+            ("Eve", Role::Morgana),
+        ]);
+        let (state, effects) = play_first_mission(
+            state,
+            &order,
+            &[("Alice", Card::Success), ("Bob", Card::Success)],
+        );
+
+        // Agravaine wasn't on the mission, so there's nothing to declare on
+        let (state, declare_effects) = state.handle_action("Dave", Action::Declare);
+        assert!(is_error(&declare_effects, GameActionError::CannotDeclare));
+
+        // Agravaine's Fail is reversed, so the mission passes and the window opens
+        let (state, effects) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[
+                ("Alice", Card::Reverse),
+                ("Bob", Card::Success),
+                ("Dave", Card::Fail),
+            ],
+        );
+        assert!(matches!(state, GameStateWrapper::Proposing(_)));
+        assert!(!effects
+            .iter()
+            .any(|effect| matches!(effect, Effect::StartTimeout(_))));
+
+        let (state, declare_effects) = state.handle_action("Dave", Action::Declare);
+        assert!(declare_effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::AgravaineDeclaration { mission: 2, player }) if player == "Dave"
+        )));
+        assert!(matches!(state, GameStateWrapper::Proposing(_)));
+        let (state, declare_effects) = state.handle_action("Dave", Action::Declare);
+        assert!(is_error(&declare_effects, GameActionError::CannotDeclare));
+
+        // Once the next proposal is made, it's too late to declare
+        let (state, effects) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[("Alice", Card::Reverse), ("Dave", Card::Fail)],
+        );
+        let (state, _) = state.handle_action(
+            &next_proposer(&effects),
+            Action::Propose {
+                players: proposal(&["Alice", "Bob", "Carol"]),
+            },
+        );
+        let (_, declare_effects) = state.handle_action("Dave", Action::Declare);
+        assert!(!declare_effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::AgravaineDeclaration { .. })
+        )));
+    }
+
+    #[test]
+    fn test_agravaine_double_fail() {
+        let (state, _, order) = start_game(&[
+            ("Alice", Role::Merlin),
+            ("Bob", Role::Percival),
+            ("Carol", Role::Lancelot),
+            ("Dave", Role::Mordred),
+            ("Eve", Role::Morgana),
+            ("Fred", Role::Agravaine),
+            ("Gina", Role::Guinevere),
+            ("Hank", Role::Titania),
+        ]);
+        let (mut state, mut effects) = play_first_mission(
+            state,
+            &order,
+            &[
+                ("Alice", Card::Success),
+                ("Bob", Card::Success),
+                ("Carol", Card::Success),
+            ],
+        );
+        for _ in 0..2 {
+            let (next_state, next_effects) = send_mission(
+                state,
+                &effects,
+                &order,
+                &[
+                    ("Alice", Card::Success),
+                    ("Bob", Card::Success),
+                    ("Carol", Card::Success),
+                    ("Dave", Card::Fail),
+                ],
+            );
+            state = next_state;
+            effects = next_effects;
+        }
+
+        // Mission 4 needs two Fails in an 8-player game, so Agravaine's Fail alone lets it pass
+        let (state, effects) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[
+                ("Alice", Card::Success),
+                ("Bob", Card::Success),
+                ("Carol", Card::Success),
+                ("Gina", Card::Success),
+                ("Fred", Card::Fail),
+            ],
+        );
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::MissionResults {
+                mission: 4,
+                fails: 1,
+                passed: true,
+                ..
+            })
+        )));
+        assert!(matches!(state, GameStateWrapper::Proposing(_)));
+
+        // Declaring fails mission 4, which is Evil's third failed mission
+        let (state, effects) = state.handle_action("Fred", Action::Declare);
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::AgravaineDeclaration { mission: 4, player }) if player == "Fred"
+        )));
+        let reveal = state.reveal().expect("Game should be over");
+        assert_eq!(reveal.winning_team, Team::Evil);
+        assert!(!reveal.missions[3].passed);
+    }
+
+    /// Plays a game where the third passed mission only passed because Agravaine's Fail was reversed, so the game
+    /// waits for Agravaine to declare.
+    fn wait_for_agravaine() -> GameStateWrapper {
+        let (state, _, order) = start_game(&[
+            ("Alice", Role::Lancelot),
+            ("Bob", Role::Percival),
+            ("Carol", Role::Merlin),
+            ("Dave", Role::Agravaine),
+            ("Eve", Role::Morgana),
+        ]);
+        let (state, effects) = play_first_mission(
+            state,
+            &order,
+            &[("Alice", Card::Success), ("Bob", Card::Success)],
+        );
+        let (state, effects) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[
+                ("Alice", Card::Success),
+                ("Bob", Card::Success),
+                ("Carol", Card::Success),
+            ],
+        );
+        let (state, effects) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[("Alice", Card::Reverse), ("Dave", Card::Fail)],
+        );
+        assert!(matches!(state, GameStateWrapper::WaitingForAgravaine(_)));
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::StartTimeout(duration) if *duration == Duration::from_secs(30)
+        )));
+        assert_eq!(legal_actions(&effects, "Dave"), vec![LegalAction::Declare]);
+        assert!(!sent_legal_actions(&effects, "Alice"));
+        assert!(state.legal_actions("Alice").is_empty());
+        state
+    }
+
+    #[test]
+    fn test_agravaine_timeout() {
+        // If Agravaine doesn't declare in time, the mission stands and Good wins the missions
+        let (state, _) = wait_for_agravaine().handle_timeout();
+        assert!(matches!(state, GameStateWrapper::Assassination(_)));
+
+        // Declaring in time fails the mission instead, so the game goes on
+        let state = wait_for_agravaine();
+        let (state, effects) = state.handle_action("Eve", Action::Declare);
+        assert!(is_error(&effects, GameActionError::CannotDeclare));
+        let (state, effects) = state.handle_action("Dave", Action::Declare);
+        assert!(effects
+            .iter()
+            .any(|effect| matches!(effect, Effect::ClearTimeout)));
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::AgravaineDeclaration { mission: 3, .. })
+        )));
+        assert!(matches!(state, GameStateWrapper::Proposing(_)));
+    }
+
+    #[test]
+    fn test_move_to_assassinate_during_agravaine() {
+        // Without turn timers, moving to assassinate must still stop Agravaine's timeout
+        let state = wait_for_agravaine();
+        let assassin = state.game().assassin.clone();
+        let (state, effects) = state.handle_action(&assassin, Action::MoveToAssassination);
+        assert!(matches!(state, GameStateWrapper::Assassination(_)));
+        assert!(effects
+            .iter()
+            .any(|effect| matches!(effect, Effect::ClearTimeout)));
+
+        // And if it fires anyway, Evil's discussion carries on
+        let (state, effects) = state.handle_timeout();
+        assert!(effects.is_empty());
+        assert_eq!(
+            state.legal_actions(&assassin).first(),
+            Some(&LegalAction::Discuss)
+        );
+    }
+
+    fn lover_info(state: &GameStateWrapper, player: &str) -> Vec<String> {
+        match state {
+            GameStateWrapper::Proposing(inner) => {
+                inner.game.info[player].get_seen_players().to_vec()
+            }
+            _ => panic!("Expected to be proposing"),
+        }
+    }
+
+    #[test]
+    fn test_lovers_reveal() {
+        let (state, _, order) = start_game(&[
+            ("Alice", Role::Merlin),
+            ("Bob", Role::Tristan),
+            ("Carol", Role::Iseult),
+            ("Dave", Role::Mordred),
+            ("Eve", Role::Morgana),
+        ]);
+        assert!(lover_info(&state, "Bob").is_empty());
+
+        let (state, effects) = play_first_mission(
+            state,
+            &order,
+            &[("Bob", Card::Success), ("Dave", Card::Fail)],
+        );
+        let is_reveal =
+            |effect: &Effect| matches!(effect, Effect::Send(_, Message::LoversRevealed { .. }));
+        assert!(!effects.iter().any(is_reveal));
+        assert!(lover_info(&state, "Bob").is_empty());
+
+        let (state, effects) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[
+                ("Alice", Card::Success),
+                ("Bob", Card::Success),
+                ("Carol", Card::Success),
+            ],
+        );
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Send(player, Message::LoversRevealed { lover }) if player == "Bob" && lover == "Carol"
+        )));
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Send(player, Message::LoversRevealed { lover }) if player == "Carol" && lover == "Bob"
+        )));
+        assert_eq!(lover_info(&state, "Bob"), vec!["Carol".to_string()]);
+        assert_eq!(lover_info(&state, "Carol"), vec!["Bob".to_string()]);
+
+        // The Lovers are only revealed once
+        let (state, effects) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[("Bob", Card::Success), ("Carol", Card::Success)],
+        );
+        assert!(!effects.iter().any(is_reveal));
+        assert_eq!(lover_info(&state, "Bob"), vec!["Carol".to_string()]);
+    }
+
+    #[test]
+    fn test_weighted_votes() {
+        let (state, _, order) = start_game(&[
+            ("Alice", Role::Arthur),
+            ("Bob", Role::Merlin),
+            ("Carol", Role::Percival),
+            ("Dave", Role::Mordred),
+            ("Eve", Role::Morgana),
+            ("Fred", Role::Lancelot),
+        ]);
+        let (state, effects) = play_first_mission(
+            state,
+            &order,
+            &[("Bob", Card::Success), ("Dave", Card::Fail)],
+        );
+        let (state, effects) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[
+                ("Bob", Card::Success),
+                ("Carol", Card::Success),
+                ("Dave", Card::Fail),
+            ],
+        );
+
+        // With two failed missions, Arthur can declare to vote twice
+        let (state, _) = state.handle_action("Alice", Action::Declare);
+        let (mut state, _) = state.handle_action(
+            &next_proposer(&effects),
+            Action::Propose {
+                players: proposal(&["Bob", "Carol", "Dave", "Eve"]),
+            },
+        );
+        let mut effects = vec![];
+        for player in order.iter() {
+            let upvote = ["Alice", "Bob", "Carol"].contains(&player.as_str());
+            let (next_state, next_effects) = state.handle_action(player, Action::Vote { upvote });
+            state = next_state;
+            effects = next_effects;
+        }
+
+        let (sent, counts, weights) = effects
+            .iter()
+            .find_map(|effect| match effect {
+                Effect::Broadcast(Message::VotingResults {
+                    sent,
+                    counts,
+                    weights,
+                }) => Some((*sent, counts, weights)),
+                _ => None,
+            })
+            .expect("Voting finished without results");
+        assert!(sent);
+        assert_eq!(
+            *counts,
+            VoteCounts::Public {
+                upvotes: proposal(&["Alice", "Bob", "Carol"]),
+                downvotes: proposal(&["Dave", "Eve", "Fred"]),
+            }
+        );
+        assert_eq!(weights["Alice"], 2);
+        assert_eq!(weights["Bob"], 1);
+        assert!(matches!(state, GameStateWrapper::OnMission(_)));
+    }
+
+    /// The legal actions most recently sent to `player` in `effects`.
+    fn legal_actions(effects: &[Effect], player: &str) -> Vec<LegalAction> {
+        effects
+            .iter()
+            .rev()
+            .find_map(|effect| match effect {
+                Effect::Send(recipient, Message::LegalActions { actions })
+                    if recipient == player =>
+                {
+                    Some(actions.clone())
+                }
+                _ => None,
+            })
+            .expect("No legal actions sent")
+    }
+
+    /// Whether `effects` sent `player` new legal actions at all.
+    fn sent_legal_actions(effects: &[Effect], player: &str) -> bool {
+        effects.iter().any(|effect| {
+            matches!(effect, Effect::Send(recipient, Message::LegalActions { .. }) if recipient == player)
+        })
+    }
+
+    #[test]
+    fn test_legal_actions() {
+        let (mut state, effects, order) = start_game(&[
+            ("Alice", Role::Merlin),
+            ("Bob", Role::Lancelot),
+            ("Carol", Role::Percival),
+            ("Dave", Role::Maeve),
+            ("Eve", Role::Morgana),
+        ]);
+        let assassin = state.game().assassin.clone();
+
+        let move_to_assassinate = |player: &str| {
+            if player == assassin {
+                vec![LegalAction::MoveToAssassination]
+            } else {
+                vec![]
+            }
+        };
+        let proposer = &order[0];
+        let mut expected = vec![LegalAction::Propose {
+            size: 2,
+            players: proposal(&["Alice", "Bob", "Carol", "Dave", "Eve"]),
+        }];
+        expected.extend(move_to_assassinate(proposer));
+        assert_eq!(legal_actions(&effects, proposer), expected);
+        assert_eq!(
+            legal_actions(&effects, &order[1]),
+            move_to_assassinate(&order[1])
+        );
+
+        // Rejected actions don't resend legal actions
+        let (next_state, effects) = state.handle_action(&order[1], Action::Vote { upvote: true });
+        assert!(is_error(&effects, GameActionError::WrongPhase));
+        state = next_state;
+
+        for proposer in order[..2].iter() {
+            let (next_state, _) = state.handle_action(
+                proposer,
+                Action::Propose {
+                    players: proposal(&["Bob", "Dave"]),
+                },
+            );
+            state = next_state;
+        }
+        let (next_state, effects) = state.handle_action("Dave", Action::Vote { upvote: true });
+        state = next_state;
+        let mut expected = vec![LegalAction::Obscure];
+        expected.extend(move_to_assassinate("Dave"));
+        assert_eq!(legal_actions(&effects, "Dave"), expected);
+        // Only the voter's actions changed, so nobody else is sent them again
+        for player in order.iter().filter(|player| *player != "Dave") {
+            assert!(!sent_legal_actions(&effects, player));
+        }
+        let mut expected = vec![LegalAction::Vote];
+        expected.extend(move_to_assassinate("Alice"));
+        assert_eq!(state.legal_actions("Alice"), expected);
+
+        let mut effects = vec![];
+        for player in order.iter().filter(|player| *player != "Dave") {
+            let (next_state, next_effects) =
+                state.handle_action(player, Action::Vote { upvote: true });
+            state = next_state;
+            effects = next_effects;
+        }
+        assert!(matches!(state, GameStateWrapper::OnMission(_)));
+        let mut expected = vec![
+            LegalAction::QuestingBeast,
+            LegalAction::Play {
+                cards: vec![Card::Success, Card::Reverse],
+            },
+        ];
+        expected.extend(move_to_assassinate("Bob"));
+        assert_eq!(legal_actions(&effects, "Bob"), expected);
+        let mut expected = vec![LegalAction::Play {
+            cards: vec![Card::Success, Card::Fail],
+        }];
+        expected.extend(move_to_assassinate("Dave"));
+        assert_eq!(legal_actions(&effects, "Dave"), expected);
+        assert_eq!(state.legal_actions("Alice"), move_to_assassinate("Alice"));
+
+        let (_, effects) = state.handle_action(
+            "Bob",
+            Action::Play {
+                card: Card::Success,
+            },
+        );
+        assert_eq!(legal_actions(&effects, "Bob"), move_to_assassinate("Bob"));
+    }
+
+    #[test]
+    fn test_assassination_legal_actions() {
+        let (state, assassin) = play_to_assassination();
+        let (state, effects) = state.handle_action(
+            &assassin,
+            Action::Discuss {
+                message: "Who's Merlin?".to_string(),
+            },
+        );
+        // Discussing doesn't change anyone's actions, so none are resent
+        assert!(!effects
+            .iter()
+            .any(|effect| matches!(effect, Effect::Send(_, Message::LegalActions { .. }))));
+
+        let options = AssassinationOptions {
+            players: proposal(&["Alice", "Bob", "Carol", "Dave", "Eve"]),
+            targets: PriorityTarget::ALL.to_vec(),
+            roles: vec![
+                Role::Merlin,
+                Role::Percival,
+                Role::Tristan,
+                Role::Iseult,
+                Role::Nimue,
+            ],
+            single_pick: false,
+        };
+        assert_eq!(
+            state.legal_actions(&assassin),
+            vec![
+                LegalAction::Discuss,
+                LegalAction::Nominate(options.clone()),
+                LegalAction::Assassinate(options),
+            ]
+        );
+        assert!(state.legal_actions("Alice").is_empty());
+    }
+
+    /// Plays a game where Good passes 3 missions, returning the state once assassination starts and the assassin.
+    fn play_to_assassination() -> (GameStateWrapper, String) {
+        let (state, _, order) = start_game(&[
+            ("Alice", Role::Merlin),
+            ("Bob", Role::Percival),
+            ("Carol", Role::Tristan),
+            ("Dave", Role::Mordred),
+            ("Eve", Role::Morgana),
+        ]);
+        let assassin = state.game().assassin.clone();
+        let (state, effects) = play_first_mission(
+            state,
+            &order,
+            &[("Alice", Card::Success), ("Bob", Card::Success)],
+        );
+
+        let (state, effects) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[
+                ("Alice", Card::Success),
+                ("Bob", Card::Success),
+                ("Carol", Card::Success),
+            ],
+        );
+        let (state, _) = send_mission(
+            state,
+            &effects,
+            &order,
+            &[("Alice", Card::Success), ("Bob", Card::Success)],
+        );
+        assert!(matches!(state, GameStateWrapper::Assassination(_)));
+        (state, assassin)
+    }
+
+    fn assassinate(
+        players: &[&str],
+        target: PriorityTarget,
+        other: Option<(&str, Role)>,
+    ) -> Action {
+        Action::Assassinate {
+            players: proposal(players),
+            target,
+            other: other.map(|(player, role)| Accusation {
+                player: player.to_string(),
+                role,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_two_target_assassination() {
+        let (state, assassin) = play_to_assassination();
+
+        let (state, effects) = state.handle_action(
+            &assassin,
+            assassinate(&["Alice"], PriorityTarget::Merlin, None),
+        );
+        assert!(is_error(&effects, GameActionError::MissingOtherTarget));
+        let (state, effects) = state.handle_action(
+            &assassin,
+            assassinate(
+                &["Alice"],
+                PriorityTarget::Merlin,
+                Some(("Carol", Role::Lancelot)),
+            ),
+        );
+        assert!(is_error(
+            &effects,
+            GameActionError::CannotAccuse {
+                role: Role::Lancelot
+            }
+        ));
+        let (state, effects) = state.handle_action(
+            &assassin,
+            assassinate(
+                &["Alice"],
+                PriorityTarget::Merlin,
+                Some(("Alice", Role::Percival)),
+            ),
+        );
+        assert!(is_error(
+            &effects,
+            GameActionError::AlreadyNamed {
+                player: "Alice".to_string(),
+                target: PriorityTarget::Merlin
+            }
+        ));
+
+        // Getting the priority target right isn't enough if the other target is wrong
+        let (state, _) = state.handle_action(
+            &assassin,
+            assassinate(
+                &["Alice"],
+                PriorityTarget::Merlin,
+                Some(("Bob", Role::Tristan)),
+            ),
+        );
+        let reveal = state.reveal().expect("Game should be over");
+        assert_eq!(reveal.winning_team, Team::Good);
+
+        let (state, assassin) = play_to_assassination();
+        let (state, _) = state.handle_action(
+            &assassin,
+            assassinate(
+                &["Alice"],
+                PriorityTarget::Merlin,
+                Some(("Bob", Role::Percival)),
+            ),
+        );
+        let reveal = state.reveal().expect("Game should be over");
+        assert_eq!(reveal.winning_team, Team::Evil);
+        let assassination = reveal.assassination.as_ref().unwrap();
+        assert!(assassination.correct);
+        assert_eq!(assassination.other.as_ref().unwrap().player, "Bob");
+    }
+
+    #[test]
+    fn test_move_to_assassinate() {
+        let (state, _, _) = start_game(&[
+            ("Alice", Role::Merlin),
+            ("Bob", Role::Percival),
+            ("Carol", Role::Tristan),
+            ("Dave", Role::Mordred),
+            ("Eve", Role::Morgana),
+        ]);
+        let assassin = state.game().assassin.clone();
+
+        let (state, effects) = state.handle_action(&assassin, Action::MoveToAssassination);
+        assert!(effects.iter().any(|effect| matches!(
+            effect,
+            Effect::Broadcast(Message::BeginAssassination {
+                moved_early: true,
+                ..
+            })
+        )));
+
+        // After moving to assassinate, a single pick has to name exactly one player
+        let (state, effects) = state.handle_action(
+            &assassin,
+            assassinate(&["Alice", "Bob"], PriorityTarget::Merlin, None),
+        );
+        assert!(is_error(&effects, GameActionError::InvalidSinglePick));
+
+        let (state, _) = state.handle_action(
+            &assassin,
+            assassinate(&["Alice"], PriorityTarget::Merlin, None),
+        );
+        let reveal = state.reveal().expect("Game should be over");
+        assert_eq!(reveal.winning_team, Team::Evil);
+        assert!(reveal.assassination.as_ref().unwrap().other.is_none());
+    }
+
+    #[test]
+    fn test_assassination_discussion() {
+        let ruleset = Ruleset {
+            discussion_timer: Some(60),
+            ..Ruleset::default()
+        };
+        let roles = vec![
+            ("Alice".to_string(), Role::Merlin),
+            ("Bob".to_string(), Role::Percival),
+            ("Carol".to_string(), Role::Tristan),
+            ("Dave".to_string(), Role::Mordred),
+            ("Eve".to_string(), Role::Morgana),
+        ];
+        let game = Game::with_roles(roles, &ruleset, 0).unwrap();
+        let assassin = game.assassin.clone();
+        let teammate = if assassin == "Dave" { "Eve" } else { "Dave" };
+        let (state, _) = GameStateWrapper::new(game);
+
+        let (state, effects) = state.handle_action(&assassin, Action::MoveToAssassination);
+        assert!(has_timer(&effects, 60));
+
+        let discuss = || Action::Discuss {
+            message: "Alice is Merlin".to_string(),
+        };
+        let (state, effects) = state.handle_action("Alice", discuss());
+        assert!(is_error(&effects, GameActionError::NotEvil));
+
+        // Nominations only go to Evil players
+        let (state, effects) = state.handle_action(
+            teammate,
+            Action::Nominate {
+                players: proposal(&["Alice"]),
+                target: PriorityTarget::Merlin,
+                other: None,
+            },
+        );
+        let recipients = effects
+            .iter()
+            .filter_map(|effect| match effect {
+                Effect::Send(player, Message::Nomination(nomination))
+                    if nomination.player == teammate =>
+                {
+                    Some(player.as_str())
+                }
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(recipients, vec!["Dave", "Eve"].into_iter().collect());
+
+        // Once Evil has discussed, a single pick is no longer allowed
+        let (state, effects) = state.handle_action(
+            &assassin,
+            assassinate(&["Alice"], PriorityTarget::Merlin, None),
+        );
+        assert!(is_error(
+            &effects,
+            GameActionError::SinglePickAfterDiscussion
+        ));
+
+        let (state, effects) = state.handle_timeout();
+        assert!(effects
+            .iter()
+            .any(|effect| matches!(effect, Effect::Send(player, Message::DiscussionOver) if player == &assassin)));
+        let (state, effects) = state.handle_action(teammate, discuss());
+        assert!(is_error(&effects, GameActionError::DiscussionOver));
+
+        let (state, _) = state.handle_action(
+            &assassin,
+            assassinate(
+                &["Alice"],
+                PriorityTarget::Merlin,
+                Some(("Bob", Role::Percival)),
+            ),
+        );
+        assert!(state.is_done());
+    }
+
+    #[test]
+    fn test_colgrevance_discussion() {
+        let roles = vec![
+            ("Alice".to_string(), Role::Merlin),
+            ("Bob".to_string(), Role::Percival),
+            ("Carol".to_string(), Role::Tristan),
+            ("Dave".to_string(), Role::Iseult),
+            ("Eve".to_string(), Role::Mordred),
+            ("Fred".to_string(), Role::Morgana),
+            ("Gina".to_string(), Role::Colgrevance),
+        ];
+        let discuss = || Action::Discuss {
+            message: "Alice is Merlin".to_string(),
+        };
+        let mut checked_assassin = false;
+        let mut checked_teammate = false;
+        for seed in 0..20 {
+            let game = Game::with_roles(roles.clone(), &Ruleset::default(), seed).unwrap();
+            let assassin = game.assassin.clone();
+            let (state, _) = GameStateWrapper::new(game);
+            let (state, _) = state.handle_action(&assassin, Action::MoveToAssassination);
+
+            if assassin == "Gina" {
+                // Everyone already knows the assassin is Evil, so a Colgrevance assassin can discuss
+                assert!(state.legal_actions("Gina").contains(&LegalAction::Discuss));
+                let (_, effects) = state.handle_action("Gina", discuss());
+                assert!(!effects
+                    .iter()
+                    .any(|effect| matches!(effect, Effect::Reply(Message::Error(_)))));
+                checked_assassin = true;
+            } else {
+                // Otherwise Colgrevance only listens, so they stay hidden from the rest of the Evil team
+                assert!(!state.legal_actions("Gina").contains(&LegalAction::Discuss));
+                let (state, effects) = state.handle_action("Gina", discuss());
+                assert!(is_error(&effects, GameActionError::ColgrevanceHidden));
+                let (_, effects) = state.handle_action(&assassin, discuss());
+                assert!(effects.iter().any(|effect| matches!(
+                    effect,
+                    Effect::Send(player, Message::EvilDiscussion { .. }) if player == "Gina"
+                )));
+                checked_teammate = true;
+            }
+        }
+        assert!(checked_assassin && checked_teammate);
+    }
+
+    #[test]
+    fn test_no_turn_timers() {
+        let game = Game::roll(
+            (1..=5).map(|n| format!("Player {}", n)).collect(),
+            &Ruleset::default(),
+        )
+        .unwrap();
+        let (state, effects) = GameStateWrapper::new(game);
+        assert!(!effects
+            .iter()
+            .any(|effect| matches!(effect, Effect::StartTimeout(_) | Effect::ClearTimeout)));
+
+        // Without a timer, a stray timeout leaves the game alone
+        let (state, effects) = state.handle_timeout();
+        assert!(effects.is_empty());
+        assert!(matches!(state, GameStateWrapper::Proposing(_)));
+    }
 }
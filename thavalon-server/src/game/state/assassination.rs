@@ -3,9 +3,19 @@ use std::collections::HashSet;
 use itertools::Itertools;
 
 use super::prelude::*;
+use crate::game::messages::{
+    Accusation, AssassinationOptions, AssassinationRecord, LegalAction, Nomination,
+};
 
 /// Phase for end-game assassination.
-pub struct Assassination {}
+pub struct Assassination {
+    /// Whether the assassin moved to assassinate before 3 missions passed
+    moved_early: bool,
+    /// Whether Evil can still discuss who to assassinate
+    discussing: bool,
+    /// Whether any Evil player has said anything or nominated targets
+    discussed: bool,
+}
 
 /*
 How assassination works now:
@@ -22,74 +32,277 @@ impl GameState<Assassination> {
         player: &str,
         target: PriorityTarget,
         players: HashSet<String>,
+        other: Option<Accusation>,
     ) -> ActionResult {
-        if player == self.game.assassin {
-            log::debug!(
-                "{} assassinated {} as {:?}",
-                player,
-                players.iter().format(" and "),
-                target
-            );
-
-            // All priority assassinations (so far) take the form of "X players are one of Y roles", so we model that as
-            // `expected_targets` and `matches` methods on `PriorityTarget` to cut down on duplication.
-            // The `None` priority target is special, because it matches no players.
-
-            if players.len() != target.expected_targets() {
-                return self.player_error(format!(
-                    "You must assassinate {} players as {:?}",
-                    target.expected_targets(),
-                    target
-                ));
+        if player != self.game.assassin {
+            return self.player_error(GameActionError::NotAssassin);
+        }
+
+        log::debug!(
+            "{} assassinated {} as {:?}{}",
+            player,
+            players.iter().format(" and "),
+            target,
+            other
+                .as_ref()
+                .map(|other| format!(", and {} as {:?}", other.player, other.role))
+                .unwrap_or_default()
+        );
+
+        for name in players
+            .iter()
+            .chain(other.iter().map(|other| &other.player))
+        {
+            if self.game.players.by_name(name).is_none() {
+                return self.player_error(GameActionError::NotInGame {
+                    player: name.clone(),
+                });
             }
+        }
+
+        let is_correct = match &other {
+            Some(other) => {
+                // All priority assassinations (so far) take the form of "X players are one of Y roles", so we model that
+                // as `expected_targets` and `matches` methods on `PriorityTarget` to cut down on duplication.
+                if players.len() != target.expected_targets() {
+                    return self.player_error(GameActionError::AssassinationSize {
+                        target,
+                        expected: target.expected_targets(),
+                    });
+                }
+                if players.contains(&other.player) {
+                    return self.player_error(GameActionError::AlreadyNamed {
+                        player: other.player.clone(),
+                        target,
+                    });
+                }
+                if !self.can_accuse(other.role) {
+                    return self.player_error(GameActionError::CannotAccuse { role: other.role });
+                }
 
-            let mut is_correct = true;
-            if target == PriorityTarget::None {
-                // If there are no assassination targets in the game, we'll have checked for that at the beginning
-                is_correct = self.game.priority_target == PriorityTarget::None
-            } else {
-                for name in players.iter() {
-                    if let Some(player) = self.game.players.by_name(name) {
-                        if !target.matches(player) {
-                            is_correct = false;
-                        }
-                    } else {
-                        return self.player_error(format!("{} is not in the game", name));
-                    }
+                let other_correct = self.game.players.is(&other.player, other.role);
+                self.is_correct_target(target, &players) && other_correct
+            }
+            None if self.phase.moved_early && self.phase.discussed => {
+                return self.player_error(GameActionError::SinglePickAfterDiscussion)
+            }
+            None if self.phase.moved_early => {
+                // After moving to assassinate, the assassin may instead make a single pick
+                if players.len() != 1 || target == PriorityTarget::None {
+                    return self.player_error(GameActionError::InvalidSinglePick);
                 }
+                self.is_correct_target(target, &players)
             }
+            None => return self.player_error(GameActionError::MissingOtherTarget),
+        };
+
+        let effects = vec![Effect::Broadcast(Message::AssassinationResult {
+            players: players.clone(),
+            target,
+            other: other.clone(),
+            correct: is_correct,
+        })];
+        let assassination = Some(AssassinationRecord {
+            assassin: player.to_string(),
+            players,
+            target,
+            other,
+            correct: is_correct,
+        });
+
+        if is_correct {
+            log::debug!("Assassination was correct!");
+            self.into_done(Team::Evil, assassination, effects)
+        } else {
+            log::debug!("Assassination was incorrect!");
+            self.into_done(Team::Good, assassination, effects)
+        }
+    }
+
+    /// Shares a message from `player` with the rest of the Evil team.
+    pub fn handle_discussion(mut self, player: &str, message: String) -> ActionResult {
+        if let Some(error) = self.check_discussion(player) {
+            return self.player_error(error);
+        }
+
+        self.phase.discussed = true;
+        let effects = self.to_evil(Message::EvilDiscussion {
+            player: player.to_string(),
+            message,
+        });
+        (GameStateWrapper::Assassination(self), effects)
+    }
 
-            let effects = vec![Effect::Broadcast(Message::AssassinationResult {
-                players,
-                target,
-                correct: is_correct,
-            })];
-
-            if is_correct {
-                log::debug!("Assassination was correct!");
-                self.into_done(Team::Evil, effects)
-            } else {
-                log::debug!("Assassination was incorrect!");
-                self.into_done(Team::Good, effects)
+    /// Shares targets suggested by `player` with the rest of the Evil team, including the assassin.
+    pub fn handle_nomination(
+        mut self,
+        player: &str,
+        target: PriorityTarget,
+        players: HashSet<String>,
+        other: Option<Accusation>,
+    ) -> ActionResult {
+        if let Some(error) = self.check_discussion(player) {
+            return self.player_error(error);
+        }
+        for name in players
+            .iter()
+            .chain(other.iter().map(|other| &other.player))
+        {
+            if self.game.players.by_name(name).is_none() {
+                return self.player_error(GameActionError::NotInGame {
+                    player: name.clone(),
+                });
             }
+        }
+
+        log::debug!(
+            "{} nominated {} as {:?}",
+            player,
+            players.iter().format(" and "),
+            target
+        );
+        self.phase.discussed = true;
+        let effects = self.to_evil(Message::Nomination(Nomination {
+            player: player.to_string(),
+            players,
+            target,
+            other,
+        }));
+        (GameStateWrapper::Assassination(self), effects)
+    }
+
+    /// Ends the discussion once its time is up. The assassin can still take as long as they need to decide.
+    pub fn handle_timeout(mut self) -> ActionResult {
+        log::debug!("Evil ran out of time to discuss the assassination");
+        self.phase.discussing = false;
+        let effects = self.to_evil(Message::DiscussionOver);
+        (GameStateWrapper::Assassination(self), effects)
+    }
+
+    /// What `player` may do during the assassination. Evil players can discuss until the discussion is over, and the
+    /// assassin can make the assassination at any point.
+    pub fn legal_actions(&self, player: &str) -> Vec<LegalAction> {
+        let options = AssassinationOptions {
+            players: self
+                .game
+                .players
+                .iter()
+                .map(|player| player.name.clone())
+                .collect(),
+            targets: PriorityTarget::ALL.to_vec(),
+            roles: self
+                .game
+                .spec
+                .good_roles
+                .iter()
+                .copied()
+                .filter(|role| self.can_accuse(*role))
+                .collect(),
+            single_pick: self.phase.moved_early && !self.phase.discussed,
+        };
+
+        let mut actions = Vec::new();
+        if self.check_discussion(player).is_none() {
+            actions.push(LegalAction::Discuss);
+            actions.push(LegalAction::Nominate(options.clone()));
+        }
+        if player == self.game.assassin {
+            actions.push(LegalAction::Assassinate(options));
+        }
+        actions
+    }
+
+    /// Checks if `player` may take part in the discussion, returning an error if not. Colgrevance reads the discussion
+    /// but can't speak in it, since that would reveal them to the rest of the Evil team. The exception is a Colgrevance
+    /// assassin, who everyone already knows is Evil.
+    fn check_discussion(&self, player: &str) -> Option<GameActionError> {
+        let role = self.game.players.by_name(player).unwrap().role;
+        if !role.is_evil() {
+            Some(GameActionError::NotEvil)
+        } else if role == Role::Colgrevance && player != self.game.assassin {
+            Some(GameActionError::ColgrevanceHidden)
+        } else if !self.phase.discussing {
+            Some(GameActionError::DiscussionOver)
         } else {
-            self.player_error("You are not the assassin")
+            None
         }
     }
+
+    /// Sends `message` to every Evil player, including Colgrevance.
+    fn to_evil(&self, message: Message) -> Vec<Effect> {
+        self.game
+            .players
+            .evil_players()
+            .iter()
+            .map(|player| Effect::Send(player.clone(), message.clone()))
+            .collect()
+    }
+
+    /// Checks if assassinating `players` as `target` is correct. The `None` priority target is special, because it
+    /// matches no players.
+    fn is_correct_target(&self, target: PriorityTarget, players: &HashSet<String>) -> bool {
+        if target == PriorityTarget::None {
+            // If there are no assassination targets in the game, we'll have checked for that at the beginning
+            self.game.priority_target == PriorityTarget::None
+        } else {
+            players.iter().all(|name| {
+                self.game
+                    .players
+                    .by_name(name)
+                    .is_some_and(|player| target.matches(player))
+            })
+        }
+    }
+
+    /// Whether the assassin may name a player as `role` alongside the priority target. Evil already knows Lancelot
+    /// appears Evil and who a declared Arthur is, so those don't count.
+    fn can_accuse(&self, role: Role) -> bool {
+        role.is_good()
+            && role != Role::Lancelot
+            && !(role == Role::Arthur && self.role_state.arthur.has_declared())
+    }
+}
+
+impl Assassination {
+    pub fn new(moved_early: bool) -> Assassination {
+        Assassination {
+            moved_early,
+            discussing: true,
+            discussed: false,
+        }
+    }
+
+    /// Whether Evil can still discuss who to assassinate
+    pub fn is_discussing(&self) -> bool {
+        self.discussing
+    }
 }
 
 impl<P: Phase> GameState<P> {
     pub fn move_to_assassinate(self, player: &str) -> ActionResult {
         if player == self.game.assassin {
             log::debug!("{} moved to assassinate", player);
-            let effects = vec![Effect::Broadcast(Message::BeginAssassination {
-                assassin: player.to_string(),
-            })];
-            let next_state = GameStateWrapper::Assassination(self.with_phase(Assassination {}));
-            (next_state, effects)
+            self.into_assassination(true, vec![])
         } else {
-            self.player_error("You are not the assassin")
+            self.player_error(GameActionError::NotAssassin)
+        }
+    }
+
+    /// Switch into the `Assassination` phase, starting Evil's discussion. The returned [`ActionResult`] will include
+    /// `effects`.
+    pub fn into_assassination(self, moved_early: bool, mut effects: Vec<Effect>) -> ActionResult {
+        effects.push(Effect::Broadcast(Message::BeginAssassination {
+            assassin: self.game.assassin.to_string(),
+            moved_early,
+        }));
+        // The assassin has no time limit, but Evil's discussion might. Even in games without turn timers, the assassin
+        // can move early while Agravaine's window is still open, so that timeout has to go.
+        match self.game.spec.turn_timers.discussion {
+            timer @ Some(_) => self.set_turn_timer(timer, &mut effects),
+            None => effects.push(Effect::ClearTimeout),
         }
+        let next_state = self.with_phase(Assassination::new(moved_early));
+        (GameStateWrapper::Assassination(next_state), effects)
     }
 }
 
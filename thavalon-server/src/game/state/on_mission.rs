@@ -1,8 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 use super::prelude::*;
 use super::RoleState;
+use crate::game::messages::LegalAction;
 
 /// Phase for when the voted-on players are going on a mission
 pub struct OnMission {
@@ -12,8 +13,8 @@ pub struct OnMission {
     /// Cards played by each player on the mission.
     cards: HashMap<String, Card>,
 
-    /// The number of questing beasts played
-    questing_beasts: usize,
+    /// Players who played the Questing Beast on this mission
+    questing_beasts: HashSet<String>,
 }
 
 /// Placeholder phase used when waiting for Agravaine to declare
@@ -28,7 +29,7 @@ impl GameState<OnMission> {
     pub fn handle_card(mut self, player: &str, card: Card) -> ActionResult {
         if self.includes_player(player) {
             if let Some(card) = self.phase.cards.get(player).cloned() {
-                self.player_error(format!("You already played a {}", card))
+                self.player_error(GameActionError::AlreadyPlayed { card })
             } else if !self
                 .game
                 .players
@@ -37,79 +38,189 @@ impl GameState<OnMission> {
                 .role
                 .can_play(card)
             {
-                self.player_error(format!("You can't play a {}", card))
+                self.player_error(GameActionError::CannotPlay { card })
             } else {
                 self.phase.cards.insert(player.to_string(), card);
                 log::debug!("{} played a {}", player, card);
 
+                let played = Effect::Broadcast(Message::CardPlayed {
+                    player: player.to_string(),
+                });
                 if self.phase.cards.len() == self.proposal().players.len() {
-                    let mission = self.mission();
-                    let passed =
-                        !is_failure(self.game.spec, mission as usize, self.phase.cards.values());
-                    log::debug!(
-                        "Mission {} {}",
-                        mission,
-                        if passed { "passed" } else { "failed" }
-                    );
-
-                    self.mission_results.push(MissionResults {
-                        passed,
-                        players: self.proposal().players.clone(),
-                    });
-
-                    let (mut successes, mut fails, mut reverses) = (0, 0, 0);
-                    for card in self.phase.cards.values() {
-                        match card {
-                            Card::Success => successes += 1,
-                            Card::Fail => fails += 1,
-                            Card::Reverse => reverses += 1,
-                        }
-                    }
-
-                    let mut effects = vec![Effect::Broadcast(Message::MissionResults {
-                        mission,
-                        successes,
-                        fails,
-                        reverses,
-                        questing_beasts: self.phase.questing_beasts,
-                        passed,
-                    })];
-                    self.add_lover_effects(&mut effects);
-
-                    // TODO: how does Agravaine work on mission 4?
-                    if self.game.spec.has_role(Role::Agravaine) && passed && fails != 0 {
-                        effects.push(Effect::StartTimeout(AGRAVAINE_TIMEOUT));
-                        let next_phase = WaitingForAgravaine {
-                            proposal_index: self.phase.proposal_index,
-                        };
-                        (
-                            GameStateWrapper::WaitingForAgravaine(self.with_phase(next_phase)),
-                            effects,
-                        )
-                    } else {
-                        let proposal_index = self.phase.proposal_index;
-                        conclude_mission(self, effects, proposal_index)
-                    }
+                    let (next_state, mut effects) = self.complete_mission();
+                    effects.insert(0, played);
+                    (next_state, effects)
                 } else {
                     // If cards aren't all in yet, there's no state change
-                    (GameStateWrapper::OnMission(self), vec![])
+                    (GameStateWrapper::OnMission(self), vec![played])
                 }
             }
         } else {
-            self.player_error("You're not on the mission")
+            self.player_error(GameActionError::NotOnMission)
+        }
+    }
+
+    /// Responds to the mission timer running out by playing a card for everyone on the mission who hasn't yet. This is
+    /// a Success if they can play one, and otherwise a Fail.
+    pub fn handle_timeout(mut self) -> ActionResult {
+        let mut missing = self
+            .proposal()
+            .players
+            .iter()
+            .filter(|player| !self.phase.cards.contains_key(*player))
+            .cloned()
+            .collect::<Vec<_>>();
+        missing.sort();
+        log::debug!("{} ran out of time to play a card", missing.join(", "));
+
+        for player in missing.iter() {
+            let role = self.game.players.by_name(player).unwrap().role;
+            let card = if role.can_play(Card::Success) {
+                Card::Success
+            } else {
+                Card::Fail
+            };
+            self.phase.cards.insert(player.clone(), card);
+        }
+
+        let (next_state, mut effects) = self.complete_mission();
+        effects.insert(
+            0,
+            Effect::Broadcast(Message::Toast {
+                severity: ToastSeverity::WARN,
+                message: format!("{} ran out of time to play a card", missing.join(", ")),
+            }),
+        );
+        (next_state, effects)
+    }
+
+    /// Ends the mission once every player on it has played a card, announcing the results.
+    fn complete_mission(mut self) -> ActionResult {
+        let mission = self.mission();
+        let cards =
+            resolve_questing_beasts(self.phase.cards.values(), self.phase.questing_beasts.len());
+        let passed = !is_failure(&self.game.spec, mission as usize, &cards);
+        log::debug!(
+            "Mission {} {}",
+            mission,
+            if passed { "passed" } else { "failed" }
+        );
+
+        let (mut successes, mut fails, mut reverses) = (0, 0, 0);
+        for card in self.phase.cards.values() {
+            match card {
+                Card::Success => successes += 1,
+                Card::Fail => fails += 1,
+                Card::Reverse => reverses += 1,
+            }
+        }
+
+        // Agravaine can declare on any mission they were on that passed anyway. Since Agravaine must play a Fail, this
+        // includes a mission 4 that needed two Fails and only got Agravaine's.
+        let agravaine_window = passed
+            && self
+                .game
+                .players
+                .by_role(Role::Agravaine)
+                .is_some_and(|agravaine| self.includes_player(&agravaine.name));
+        self.mission_results.push(MissionResults {
+            passed,
+            players: self.proposal().players.clone(),
+            proposal: self.phase.proposal_index,
+            cards: self.phase.cards.clone(),
+            questing_beasts: self.phase.questing_beasts.len(),
+            agravaine_window,
+            agravaine_declaration: None,
+        });
+
+        let mut effects = vec![Effect::Broadcast(Message::MissionResults {
+            mission,
+            successes,
+            fails,
+            reverses,
+            questing_beasts: self.phase.questing_beasts.len(),
+            passed,
+        })];
+        self.add_lover_effects(&mut effects);
+        self.reveal_lovers(&mut effects);
+
+        let decides_game = self
+            .mission_results
+            .iter()
+            .filter(|mission| mission.passed)
+            .count()
+            == 3;
+        if agravaine_window && decides_game {
+            // There won't be another proposal to close the window, so Agravaine gets a limited time to declare instead
+            effects.push(Effect::StartTimeout(AGRAVAINE_TIMEOUT));
+            let next_phase = WaitingForAgravaine {
+                proposal_index: self.phase.proposal_index,
+            };
+            (
+                GameStateWrapper::WaitingForAgravaine(self.with_phase(next_phase)),
+                effects,
+            )
+        } else {
+            if agravaine_window {
+                self.role_state.agravaine.open_window(mission);
+            }
+            let proposal_index = self.phase.proposal_index;
+            conclude_mission(self, effects, proposal_index)
         }
     }
 
+    /// Plays the Questing Beast for `player`, which cancels out a Reverse when the mission resolves. It has to be
+    /// played before the player's mission card, since the mission may resolve as soon as that card is in.
     pub fn handle_questing_beast(mut self, player: &str) -> ActionResult {
-        if self.includes_player(player) {
+        if !self.includes_player(player) {
+            self.player_error(GameActionError::NotOnMission)
+        } else if !self
+            .game
+            .players
+            .by_name(player)
+            .unwrap()
+            .role
+            .can_play_questing_beast()
+        {
+            self.player_error(GameActionError::CannotPlayQuestingBeast)
+        } else if self.phase.cards.contains_key(player) {
+            self.player_error(GameActionError::QuestingBeastAfterCard)
+        } else if self.phase.questing_beasts.contains(player) {
+            self.player_error(GameActionError::QuestingBeastAlreadyPlayed)
+        } else if !self.role_state.questing_beast.can_play(player) {
+            self.player_error(GameActionError::NoQuestingBeastsLeft)
+        } else {
             log::debug!("{} played a questing beast", player);
-            self.phase.questing_beasts += 1;
+            self.role_state.questing_beast.mark_played(player);
+            self.phase.questing_beasts.insert(player.to_string());
             (GameStateWrapper::OnMission(self), vec![])
-        } else {
-            self.player_error("You're not on the mission")
         }
     }
 
+    /// What `player` may do on the mission. Players can only act before playing their mission card.
+    pub fn legal_actions(&self, player: &str) -> Vec<LegalAction> {
+        let mut actions = Vec::new();
+        if !self.includes_player(player) || self.phase.cards.contains_key(player) {
+            return actions;
+        }
+
+        let role = self.game.players.by_name(player).unwrap().role;
+        if role.can_play_questing_beast()
+            && !self.phase.questing_beasts.contains(player)
+            && self.role_state.questing_beast.can_play(player)
+        {
+            actions.push(LegalAction::QuestingBeast);
+        }
+        actions.push(LegalAction::Play {
+            cards: [Card::Success, Card::Fail, Card::Reverse]
+                .iter()
+                .copied()
+                .filter(|card| role.can_play(*card))
+                .collect(),
+        });
+        actions
+    }
+
     /// The propopsal this mission is based on
     fn proposal(&self) -> &Proposal {
         self.proposals
@@ -150,6 +261,37 @@ impl GameState<OnMission> {
         }
     }
 
+    // Once Tristan and Iseult have been on a mission together, they learn who each other are for the rest of the game.
+    fn reveal_lovers(&mut self, effects: &mut Vec<Effect>) {
+        if self.role_state.lovers.is_revealed() {
+            return;
+        }
+        let (tristan, iseult) = match (
+            self.game.display_name_from_role(Role::Tristan),
+            self.game.display_name_from_role(Role::Iseult),
+        ) {
+            (Some(tristan), Some(iseult)) => (tristan.clone(), iseult.clone()),
+            _ => return,
+        };
+        if !self.includes_player(&tristan) || !self.includes_player(&iseult) {
+            return;
+        }
+
+        log::debug!("Revealing {} and {} to each other", tristan, iseult);
+        self.role_state.lovers.reveal();
+        for (player, lover) in [(&tristan, &iseult), (&iseult, &tristan)].iter() {
+            if let Some(info) = self.game.info.get_mut(*player) {
+                info.reveal(lover);
+            }
+            effects.push(Effect::Send(
+                player.to_string(),
+                Message::LoversRevealed {
+                    lover: lover.to_string(),
+                },
+            ));
+        }
+    }
+
     // Builds a message for a player, given information about their lover who may not exist.
     fn build_lover_effect(
         &self,
@@ -190,43 +332,30 @@ impl OnMission {
         OnMission {
             proposal_index,
             cards: HashMap::new(),
-            questing_beasts: 0,
+            questing_beasts: HashSet::new(),
         }
     }
 }
 
 impl GameState<WaitingForAgravaine> {
     pub fn handle_declaration(mut self, player: &str) -> ActionResult {
-        // Subtract one since the mission Agravaine failing is the one prior to the current propposal.
-        let mission_number = self.mission() - 1;
-        let mission = self
-            .mission_results
-            .last_mut()
-            .expect("Waiting for Agravaine but no mission went");
-        if mission.players.contains(player) && self.game.players.is(player, Role::Agravaine) {
-            log::debug!(
-                "Agravaine declaration by {} caused mission {} to fail",
-                player,
-                mission_number
-            );
-            mission.passed = false;
-
-            let effects = vec![
-                Effect::Broadcast(Message::AgravaineDeclaration {
-                    mission: mission_number,
-                    player: player.to_string(),
-                }),
-                Effect::Broadcast(Message::Toast {
-                    severity: ToastSeverity::URGENT,
-                    message: format!("{} has declared as Agravaine!", player),
-                }),
-                Effect::ClearTimeout,
-            ];
-
+        let mission = self.mission() - 1;
+        if self.game.players.is(player, Role::Agravaine) {
+            let mut effects = declare_agravaine(&mut self, mission, player);
+            effects.push(Effect::ClearTimeout);
             let proposal = self.phase.proposal_index;
             conclude_mission(self, effects, proposal)
         } else {
-            self.player_error("You can't declare right now")
+            self.player_error(GameActionError::CannotDeclare)
+        }
+    }
+
+    /// Only Agravaine can act while the game waits for them.
+    pub fn legal_actions(&self, player: &str) -> Vec<LegalAction> {
+        if self.game.players.is(player, Role::Agravaine) {
+            vec![LegalAction::Declare]
+        } else {
+            Vec::new()
         }
     }
 
@@ -237,9 +366,58 @@ impl GameState<WaitingForAgravaine> {
     }
 }
 
+impl GameState<Proposing> {
+    /// Handles an Agravaine declaration on the previous mission, which is allowed until the next proposal is made.
+    pub fn handle_agravaine_declaration(mut self, player: &str) -> ActionResult {
+        match self.role_state.agravaine.close_window() {
+            Some(mission) => {
+                let effects = declare_agravaine(&mut self, mission, player);
+                if self.mission_results.iter().filter(|m| !m.passed).count() == 3 {
+                    log::debug!("3 missions have failed, the Evil team has won");
+                    self.into_done(Team::Evil, None, effects)
+                } else {
+                    (GameStateWrapper::Proposing(self), effects)
+                }
+            }
+            None => self.player_error(GameActionError::CannotDeclare),
+        }
+    }
+}
+
 impl_phase!(OnMission);
 impl_phase!(WaitingForAgravaine);
 
+/// Fails `mission` because `player` declared as Agravaine, returning the effects announcing it. This assumes the
+/// declaration is allowed.
+fn declare_agravaine<P: Phase>(
+    state: &mut GameState<P>,
+    mission: MissionNumber,
+    player: &str,
+) -> Vec<Effect> {
+    log::debug!(
+        "Agravaine declaration by {} caused mission {} to fail",
+        player,
+        mission
+    );
+    let results = state
+        .mission_results
+        .get_mut(mission as usize - 1)
+        .expect("Agravaine declared on a mission that didn't go");
+    results.passed = false;
+    results.agravaine_declaration = Some(player.to_string());
+
+    vec![
+        Effect::Broadcast(Message::AgravaineDeclaration {
+            mission,
+            player: player.to_string(),
+        }),
+        Effect::Broadcast(Message::Toast {
+            severity: ToastSeverity::URGENT,
+            message: format!("{} has declared as Agravaine!", player),
+        }),
+    ]
+}
+
 /// Common logic for transitioning to the next phase after a mission ends. This is shared by the [`OnMission`] and
 /// [`WaitingForAgravaine`] phases. This assumes that `state.mission_results` is up-to-date (including Agravaine
 /// declarations).
@@ -264,14 +442,10 @@ fn conclude_mission<P: Phase>(
 
     if successes == 3 {
         log::debug!("3 missions have passed, moving to assassination");
-        effects.push(Effect::Broadcast(Message::BeginAssassination {
-            assassin: state.game.assassin.to_string(),
-        }));
-        let next_state = GameStateWrapper::Assassination(state.with_phase(Assassination {}));
-        (next_state, effects)
+        state.into_assassination(false, effects)
     } else if fails == 3 {
         log::debug!("3 missions have failed, the Evil team has won");
-        state.into_done(Team::Evil, effects)
+        state.into_done(Team::Evil, None, effects)
     } else {
         let mission = state.mission();
         let next_proposer = if mission == 2 {
@@ -290,6 +464,25 @@ fn conclude_mission<P: Phase>(
     }
 }
 
+/// Applies Questing Beasts to the cards played on a mission. Each Questing Beast cancels out one Reverse, which then
+/// counts as a Success.
+fn resolve_questing_beasts<'a, I: IntoIterator<Item = &'a Card>>(
+    cards: I,
+    questing_beasts: usize,
+) -> Vec<Card> {
+    let mut remaining = questing_beasts;
+    cards
+        .into_iter()
+        .map(|card| match card {
+            Card::Reverse if remaining > 0 => {
+                remaining -= 1;
+                Card::Success
+            }
+            card => *card,
+        })
+        .collect()
+}
+
 /// Tests if a mission has failed
 /// Note: this is written the way it is because it's easier to express the rules for a mission failing. In general,
 /// it's clearer to keep track of whether or not it passed.
@@ -321,7 +514,7 @@ fn is_failure<'a, I: IntoIterator<Item = &'a Card>>(
 #[cfg(test)]
 mod test {
     use super::super::prelude::*;
-    use super::is_failure;
+    use super::{is_failure, resolve_questing_beasts};
 
     #[test]
     fn test_is_failure() {
@@ -408,4 +601,28 @@ mod test {
             &[Card::Reverse, Card::Reverse, Card::Fail, Card::Fail]
         ));
     }
+
+    #[test]
+    fn test_questing_beasts() {
+        let spec = GameSpec::for_players(5).unwrap();
+
+        // A Questing Beast cancels out a Reverse
+        let cards = resolve_questing_beasts(&[Card::Success, Card::Reverse], 1);
+        assert_eq!(cards, vec![Card::Success, Card::Success]);
+        assert!(!is_failure(spec, 1, &cards));
+
+        // ...but not a Fail
+        let cards = resolve_questing_beasts(&[Card::Fail, Card::Reverse], 1);
+        assert!(is_failure(spec, 1, &cards));
+
+        // Cancelling one of two Reverses means the mission is reversed again
+        let cards = resolve_questing_beasts(&[Card::Reverse, Card::Reverse, Card::Fail], 1);
+        assert!(!is_failure(spec, 1, &cards));
+
+        // Extra Questing Beasts have no effect
+        let cards = resolve_questing_beasts(&[Card::Success, Card::Reverse], 2);
+        assert!(!is_failure(spec, 1, &cards));
+        let cards = resolve_questing_beasts(&[Card::Success, Card::Fail], 2);
+        assert!(is_failure(spec, 1, &cards));
+    }
 }
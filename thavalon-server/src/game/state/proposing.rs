@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
 use super::prelude::*;
+use crate::game::messages::LegalAction;
 
 /// Phase for waiting for a player to make a mission proposal.
 pub struct Proposing {
@@ -9,8 +10,6 @@ pub struct Proposing {
     selected_players: HashSet<String>,
 }
 
-const NOT_PROPOSER_ERROR: &str = "It's not your proposal";
-
 impl GameState<Proposing> {
     /// Respond to the proposer adding a player to their proposal. If the player performing the action
     /// is not the proposer, this sends them an error message. It validates that the added player is
@@ -18,10 +17,10 @@ impl GameState<Proposing> {
     /// correct size, since final validation is done when the proposal is submitted.
     pub fn handle_player_selected(mut self, player: &str, added_player: String) -> ActionResult {
         if player != self.phase.proposer {
-            return self.player_error(NOT_PROPOSER_ERROR);
+            return self.player_error(GameActionError::NotProposer);
         }
 
-        if let Some(error) = self.validate_player(player) {
+        if let Some(error) = self.validate_player(&added_player) {
             return self.player_error(error);
         }
 
@@ -42,11 +41,13 @@ impl GameState<Proposing> {
         removed_player: String,
     ) -> ActionResult {
         if player != self.phase.proposer {
-            return self.player_error(NOT_PROPOSER_ERROR);
+            return self.player_error(GameActionError::NotProposer);
         }
 
         if self.game.players.by_name(&removed_player).is_none() {
-            return self.player_error(format!("{} is not in the game!", removed_player));
+            return self.player_error(GameActionError::NotInGame {
+                player: removed_player,
+            });
         }
 
         if self.phase.selected_players.remove(&removed_player) {
@@ -56,7 +57,9 @@ impl GameState<Proposing> {
 
             (GameStateWrapper::Proposing(self), effects)
         } else {
-            self.player_error(format!("{} wasn't on the proposal!", removed_player))
+            self.player_error(GameActionError::NotOnProposal {
+                player: removed_player,
+            })
         }
     }
 
@@ -66,13 +69,16 @@ impl GameState<Proposing> {
     /// and go straight to [`OnMission`].
     pub fn handle_proposal(mut self, player: &str, players: HashSet<String>) -> ActionResult {
         if player != self.phase.proposer {
-            return self.player_error(NOT_PROPOSER_ERROR);
+            return self.player_error(GameActionError::NotProposer);
         }
 
         let mission = self.mission();
         let expected_size = self.game.spec.mission_size(mission);
         if players.len() != expected_size {
-            return self.player_error(format!("Proposal must contain {} players", expected_size));
+            return self.player_error(GameActionError::ProposalSize {
+                expected: expected_size,
+                actual: players.len(),
+            });
         }
 
         for player in players.iter() {
@@ -82,11 +88,16 @@ impl GameState<Proposing> {
         }
 
         let proposal = Proposal {
+            mission,
             proposer: player.to_string(),
             players: players.clone(),
+            votes: None,
+            obscured: false,
         };
         log::debug!("Got {} for mission {}", proposal, mission);
         self.proposals.push(proposal);
+        // Agravaine can only declare on the previous mission until the next proposal is made
+        self.role_state.agravaine.close_window();
 
         let mut effects = vec![Effect::Broadcast(Message::ProposalMade {
             proposer: player.to_string(),
@@ -106,6 +117,7 @@ impl GameState<Proposing> {
                 mission,
                 players: proposal.players.clone(),
             }));
+            self.set_turn_timer(self.game.spec.turn_timers.mission, &mut effects);
             let next_phase = OnMission::new(self.proposals.len() - 1);
             (
                 GameStateWrapper::OnMission(self.with_phase(next_phase)),
@@ -127,25 +139,67 @@ impl GameState<Proposing> {
             }
 
             effects.push(Effect::Broadcast(Message::CommenceVoting));
+            self.set_turn_timer(self.game.spec.turn_timers.voting, &mut effects);
             let next_state = self.with_phase(Voting::new());
             (GameStateWrapper::Voting(next_state), effects)
         }
     }
 
-    /// Checks if `player` is allowed on this proposal, returning an error message if not.
-    fn validate_player(&self, player_name: &str) -> Option<String> {
+    /// Responds to the proposal timer running out by passing the proposal to the next player. The skipped proposal
+    /// does not count towards force.
+    pub fn handle_timeout(self) -> ActionResult {
+        let proposer = self.phase.proposer.clone();
+        log::debug!("{} ran out of time to propose", proposer);
+        let effects = vec![Effect::Broadcast(Message::Toast {
+            severity: ToastSeverity::WARN,
+            message: format!("{} ran out of time to propose", proposer),
+        })];
+        let next_proposer = self.game.next_proposer(&proposer).to_string();
+        self.into_proposing(next_proposer, effects)
+    }
+
+    /// What `player` may do while waiting on a proposal. Agravaine may also be able to declare on the previous
+    /// mission.
+    pub fn legal_actions(&self, player: &str) -> Vec<LegalAction> {
+        let mut actions = Vec::new();
+        if player == self.phase.proposer {
+            actions.push(LegalAction::Propose {
+                size: self.game.spec.mission_size(self.mission()),
+                players: self
+                    .game
+                    .players
+                    .iter()
+                    .filter(|candidate| self.validate_player(&candidate.name).is_none())
+                    .map(|candidate| candidate.name.clone())
+                    .collect(),
+            });
+        }
+        if self.role_state.agravaine.window().is_some()
+            && self.game.players.is(player, Role::Agravaine)
+        {
+            actions.push(LegalAction::Declare);
+        }
+        actions
+    }
+
+    /// Checks if `player` is allowed on this proposal, returning an error if not.
+    fn validate_player(&self, player_name: &str) -> Option<GameActionError> {
         match self.game.players.by_name(player_name) {
             Some(player) => {
                 if player.role == Role::Arthur
                     && self.role_state.arthur.has_declared()
                     && self.mission() != 5
                 {
-                    Some(format!("Arthur cannot go until mission 5"))
+                    Some(GameActionError::IneligiblePlayer {
+                        player: player_name.to_string(),
+                    })
                 } else {
                     None
                 }
             }
-            None => Some(format!("{} is not in the game", player_name)),
+            None => Some(GameActionError::NotInGame {
+                player: player_name.to_string(),
+            }),
         }
     }
 }
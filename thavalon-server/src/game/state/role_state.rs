@@ -1,10 +1,19 @@
 //! Tracks game state related to individual roles, such as how many uses of an ability are left.
 
+use std::collections::HashMap;
+
 use super::prelude::*;
 
+/// How many times each player may play the Questing Beast in a game. Like the rest of the Questing Beast, this is a
+/// house rule pending sign-off (see `docs/rules.md`).
+const QUESTING_BEASTS_PER_PLAYER: usize = 1;
+
 pub struct RoleState {
     pub maeve: MaeveState,
     pub arthur: ArthurState,
+    pub questing_beast: QuestingBeastState,
+    pub agravaine: AgravaineState,
+    pub lovers: LoversState,
 }
 
 pub struct MaeveState {
@@ -16,11 +25,29 @@ pub struct ArthurState {
     has_declared: bool,
 }
 
+pub struct AgravaineState {
+    /// The mission Agravaine may still declare on, if any. This stays open until the next proposal is made.
+    window: Option<MissionNumber>,
+}
+
+pub struct LoversState {
+    /// Whether Tristan and Iseult have been on a mission together, revealing them to each other
+    revealed: bool,
+}
+
+pub struct QuestingBeastState {
+    /// How many Questing Beasts each player has played
+    played: HashMap<String, usize>,
+}
+
 impl RoleState {
     pub fn new(game: &Game) -> RoleState {
         RoleState {
-            maeve: MaeveState::new(game.spec),
+            maeve: MaeveState::new(&game.spec),
             arthur: ArthurState::new(),
+            questing_beast: QuestingBeastState::new(),
+            agravaine: AgravaineState::new(),
+            lovers: LoversState { revealed: false },
         }
     }
 
@@ -28,8 +55,24 @@ impl RoleState {
     /// some roles require the entire game state to update.
     pub fn on_round_start<P: Phase>(state: &mut GameState<P>, effects: &mut Vec<Effect>) {
         state.role_state.maeve.on_round_start();
+        if let Some(maeve) = state.game.players.by_role(Role::Maeve) {
+            effects.push(Effect::Send(
+                maeve.name.clone(),
+                state.role_state.maeve.status(),
+            ));
+        }
         state.role_state.arthur.on_round_start(state, effects);
     }
+
+    /// How many votes a player with `role` casts on each proposal. Any role that changes its vote weight should do so
+    /// here, so that every vote is counted the same way.
+    pub fn vote_weight(&self, role: Role) -> u32 {
+        if role == Role::Arthur && self.arthur.has_declared() {
+            2
+        } else {
+            1
+        }
+    }
 }
 
 impl MaeveState {
@@ -49,6 +92,14 @@ impl MaeveState {
         !self.obscured_this_round && self.obscures_remaining > 0
     }
 
+    /// The message telling Maeve whether she can use her ability.
+    pub fn status(&self) -> Message {
+        Message::ObscureStatus {
+            can_obscure: self.can_obscure(),
+            obscures_remaining: self.obscures_remaining,
+        }
+    }
+
     /// Records when Maeve uses her ability.
     pub fn mark_obscure(&mut self) {
         self.obscured_this_round = true;
@@ -94,3 +145,54 @@ impl ArthurState {
         }
     }
 }
+
+impl AgravaineState {
+    fn new() -> AgravaineState {
+        AgravaineState { window: None }
+    }
+
+    /// Lets Agravaine declare on `mission` until the window is closed.
+    pub fn open_window(&mut self, mission: MissionNumber) {
+        self.window = Some(mission);
+    }
+
+    /// The mission Agravaine can currently declare on, if any.
+    pub fn window(&self) -> Option<MissionNumber> {
+        self.window
+    }
+
+    /// Closes the declaration window, returning the mission it was open for.
+    pub fn close_window(&mut self) -> Option<MissionNumber> {
+        self.window.take()
+    }
+}
+
+impl LoversState {
+    /// Checks if the Lovers have been revealed to each other
+    pub fn is_revealed(&self) -> bool {
+        self.revealed
+    }
+
+    /// Records when the Lovers go on a mission together.
+    pub fn reveal(&mut self) {
+        self.revealed = true;
+    }
+}
+
+impl QuestingBeastState {
+    fn new() -> QuestingBeastState {
+        QuestingBeastState {
+            played: HashMap::new(),
+        }
+    }
+
+    /// Checks if `player` has any Questing Beasts left to play
+    pub fn can_play(&self, player: &str) -> bool {
+        self.played.get(player).copied().unwrap_or(0) < QUESTING_BEASTS_PER_PLAYER
+    }
+
+    /// Records when `player` plays a Questing Beast.
+    pub fn mark_played(&mut self, player: &str) {
+        *self.played.entry(player.to_string()).or_insert(0) += 1;
+    }
+}
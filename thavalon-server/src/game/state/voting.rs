@@ -1,6 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
+use itertools::Itertools;
+
 use super::prelude::*;
+use crate::game::messages::LegalAction;
 
 /// Phase for voting on a mission proposal
 pub struct Voting {
@@ -13,7 +16,7 @@ pub struct Voting {
 impl GameState<Voting> {
     pub fn handle_vote(mut self, player: &str, is_upvote: bool) -> ActionResult {
         if self.phase.votes.contains_key(player) {
-            return self.player_error("You already voted");
+            return self.player_error(GameActionError::AlreadyVoted);
         }
 
         log::debug!(
@@ -23,104 +26,168 @@ impl GameState<Voting> {
         );
         self.phase.votes.insert(player.to_string(), is_upvote);
 
-        let mut effects = vec![Effect::Broadcast(Message::VoteReceived)];
+        let effects = vec![Effect::Broadcast(Message::VoteReceived {
+            player: player.to_string(),
+        })];
 
         if self.phase.votes.len() == self.game.size() {
-            let mission = self.mission();
-
-            let mut upvotes = HashSet::new();
-            let mut downvotes = HashSet::new();
-
-            for (player, vote) in self.phase.votes.drain() {
-                let is_arthur = self.game.players.is(&player, Role::Arthur)
-                    && self.role_state.arthur.has_declared();
-
-                let collection = if vote { &mut upvotes } else { &mut downvotes };
-
-                if is_arthur {
-                    collection.insert(format!("{} (Arthur)", player));
-                }
-                collection.insert(player);
-            }
+            self.count_votes(effects)
+        } else {
+            // If we don't have all the votes yet, there's no state change
+            (GameStateWrapper::Voting(self), effects)
+        }
+    }
 
-            let sent = upvotes.len() > downvotes.len();
+    /// Responds to the voting timer running out by counting any missing votes as downvotes.
+    pub fn handle_timeout(mut self) -> ActionResult {
+        let mut missing = self
+            .game
+            .players
+            .iter()
+            .map(|player| player.name.clone())
+            .filter(|player| !self.phase.votes.contains_key(player))
+            .collect::<Vec<_>>();
+        missing.sort();
+
+        let missing_list = missing.iter().format(", ").to_string();
+        log::debug!("{} ran out of time to vote", missing_list);
+        let effects = vec![Effect::Broadcast(Message::Toast {
+            severity: ToastSeverity::WARN,
+            message: format!("{} ran out of time and downvoted", missing_list),
+        })];
+
+        for player in missing {
+            self.phase.votes.insert(player, false);
+        }
+        self.count_votes(effects)
+    }
 
-            // TODO: This probably could be cleaner, but hacking this for pre-alpha.
-            if self.phase.obscured {
-                effects.push(Effect::Broadcast(Message::Toast {
-                    severity: ToastSeverity::WARN,
-                    message: format!(
-                        "Mission {}: Maeve has obscured the votes!\nUpvotes: {}\nDownvotes: {}",
-                        mission,
-                        upvotes.len(),
-                        downvotes.len()
-                    ),
-                }));
+    /// Tallies the votes once every player has voted, and either sends the mission or moves on to the next proposal.
+    fn count_votes(mut self, mut effects: Vec<Effect>) -> ActionResult {
+        let mission = self.mission();
+
+        let mut upvotes = HashSet::new();
+        let mut downvotes = HashSet::new();
+        let (mut upvote_total, mut downvote_total) = (0, 0);
+        let mut weights = HashMap::with_capacity(self.phase.votes.len());
+
+        // Keep the real votes for the post-game reveal, even if they're obscured now
+        let proposal = self.proposals.last_mut().expect("Voted with no proposals!");
+        proposal.votes = Some(self.phase.votes.clone());
+        proposal.obscured = self.phase.obscured;
+
+        for (player, vote) in self.phase.votes.drain() {
+            let role = self
+                .game
+                .players
+                .by_name(&player)
+                .expect("Vote from a player not in the game")
+                .role;
+            let weight = self.role_state.vote_weight(role);
+            if vote {
+                upvote_total += weight;
+                upvotes.insert(player.clone());
+            } else {
+                downvote_total += weight;
+                downvotes.insert(player.clone());
             }
+            weights.insert(player, weight);
+        }
 
-            let vote_counts = if self.phase.obscured {
-                messages::VoteCounts::Obscured {
-                    upvotes: upvotes.len() as u32,
-                    downvotes: downvotes.len() as u32,
-                }
-            } else {
-                messages::VoteCounts::Public { upvotes, downvotes }
-            };
+        let sent = upvote_total > downvote_total;
 
-            effects.push(Effect::Broadcast(Message::VotingResults {
-                sent,
-                counts: vote_counts,
+        // TODO: This probably could be cleaner, but hacking this for pre-alpha.
+        if self.phase.obscured {
+            effects.push(Effect::Broadcast(Message::Toast {
+                severity: ToastSeverity::WARN,
+                message: format!(
+                    "Mission {}: Maeve has obscured the votes!\nUpvotes: {}\nDownvotes: {}",
+                    mission, upvote_total, downvote_total
+                ),
             }));
+        }
 
-            if mission == 1 {
-                let proposal_index = if sent { 0 } else { 1 };
-                let proposal = &self.proposals[proposal_index];
-                log::debug!("Voted to send {} on mission 1", proposal);
+        let vote_counts = if self.phase.obscured {
+            messages::VoteCounts::Obscured {
+                upvotes: upvote_total,
+                downvotes: downvote_total,
+            }
+        } else {
+            messages::VoteCounts::Public { upvotes, downvotes }
+        };
+
+        effects.push(Effect::Broadcast(Message::VotingResults {
+            sent,
+            counts: vote_counts,
+            weights,
+        }));
+
+        if mission == 1 {
+            let proposal_index = if sent { 0 } else { 1 };
+            let proposal = &self.proposals[proposal_index];
+            log::debug!("Voted to send {} on mission 1", proposal);
+            effects.push(Effect::Broadcast(Message::MissionGoing {
+                mission,
+                players: proposal.players.clone(),
+            }));
+            self.set_turn_timer(self.game.spec.turn_timers.mission, &mut effects);
+            let next_state = self.with_phase(OnMission::new(proposal_index));
+            (GameStateWrapper::OnMission(next_state), effects)
+        } else {
+            let proposal = self.proposals.last().expect("Voted with no proposals!");
+            if sent {
+                log::debug!("Voted to send {} on mission {}", proposal, mission);
                 effects.push(Effect::Broadcast(Message::MissionGoing {
                     mission,
                     players: proposal.players.clone(),
                 }));
+                self.set_turn_timer(self.game.spec.turn_timers.mission, &mut effects);
+                let proposal_index = self.proposals.len() - 1;
                 let next_state = self.with_phase(OnMission::new(proposal_index));
                 (GameStateWrapper::OnMission(next_state), effects)
             } else {
-                let proposal = self.proposals.last().expect("Voted with no proposals!");
-                if sent {
-                    log::debug!("Voted to send {} on mission {}", proposal, mission);
-                    effects.push(Effect::Broadcast(Message::MissionGoing {
-                        mission,
-                        players: proposal.players.clone(),
-                    }));
-                    let proposal_index = self.proposals.len() - 1;
-                    let next_state = self.with_phase(OnMission::new(proposal_index));
-                    (GameStateWrapper::OnMission(next_state), effects)
-                } else {
-                    log::debug!("Voted not to send {}", proposal);
-
-                    let next_proposer = self.game.next_proposer(&proposal.proposer).to_string();
-                    self.into_proposing(next_proposer, effects)
-                }
+                log::debug!("Voted not to send {}", proposal);
+
+                let next_proposer = self.game.next_proposer(&proposal.proposer).to_string();
+                self.into_proposing(next_proposer, effects)
             }
-        } else {
-            // If we don't have all the votes yet, there's no state change
-            (GameStateWrapper::Voting(self), effects)
         }
     }
 
     pub fn handle_obscure(mut self, player: &str) -> ActionResult {
         if self.game.players.by_name(player).unwrap().role == Role::Maeve {
             if self.phase.obscured {
-                self.player_error("You already obscured the votes for this proposal")
+                self.player_error(GameActionError::AlreadyObscured)
             } else if self.role_state.maeve.can_obscure() {
                 log::debug!("Maeve obscured the votes!");
                 self.role_state.maeve.mark_obscure();
                 self.phase.obscured = true;
-                (GameStateWrapper::Voting(self), vec![])
+                let status = self.role_state.maeve.status();
+                (
+                    GameStateWrapper::Voting(self),
+                    vec![Effect::Send(player.to_string(), status)],
+                )
             } else {
-                self.player_error("You can't obscure this round")
+                self.player_error(GameActionError::ObscureUnavailable)
             }
         } else {
-            self.player_error("You can't obscure votes")
+            self.player_error(GameActionError::CannotObscure)
+        }
+    }
+
+    /// What `player` may do while voting is open. Maeve can obscure the votes even after voting herself.
+    pub fn legal_actions(&self, player: &str) -> Vec<LegalAction> {
+        let mut actions = Vec::new();
+        if !self.phase.votes.contains_key(player) {
+            actions.push(LegalAction::Vote);
+        }
+        if self.game.players.is(player, Role::Maeve)
+            && !self.phase.obscured
+            && self.role_state.maeve.can_obscure()
+        {
+            actions.push(LegalAction::Obscure);
         }
+        actions
     }
 
     /// Cancels voting, returning to the player who had been proposing. This is used for Arthur declarations while voting, since
@@ -7,12 +7,13 @@ use crate::game::{Action, Message};
 use std::collections::HashMap;
 
 use futures::{
-    future::{AbortHandle, Abortable},
+    future::{self, AbortHandle, Abortable},
     stream::SplitSink,
-    SinkExt, StreamExt,
+    SinkExt, Stream, StreamExt,
 };
 
 use tokio::{
+    sync::broadcast,
     sync::mpsc::{self, Receiver, Sender},
     task,
     time::{delay_for, Duration},
@@ -49,6 +50,7 @@ pub struct PlayerClient {
     to_game: Sender<Action>,
     to_outbound_task: Sender<OutboundTaskMessageType>,
     oubound_task_receiver: Option<Receiver<OutboundTaskMessageType>>,
+    is_spectator: bool,
 }
 
 // Implement drop to clean up all outstanding tasks.
@@ -89,12 +91,47 @@ impl PlayerClient {
             to_game,
             to_outbound_task: to_outbound_task_tx,
             oubound_task_receiver: Some(to_outbound_task_rx),
+            is_spectator: false,
         };
 
         client.spawn_from_game_task(from_game);
         client
     }
 
+    /// Creates a new PlayerClient for a spectator. Spectators can't start the game or send game commands, and only
+    /// receive game messages once `spectate` is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_id` - The ID for this client
+    /// * `to_lobby` - A `LobbyChannel` back to the owning lobby
+    pub fn new_spectator(client_id: String, to_lobby: LobbyChannel) -> Self {
+        let (to_outbound_task_tx, to_outbound_task_rx) =
+            mpsc::channel::<OutboundTaskMessageType>(10);
+        // Spectators never talk to the game, so nothing listens on the other end of this channel.
+        let (to_game, _) = mpsc::channel(1);
+        PlayerClient {
+            client_id,
+            tasks: HashMap::new(),
+            to_lobby,
+            to_game,
+            to_outbound_task: to_outbound_task_tx,
+            oubound_task_receiver: Some(to_outbound_task_rx),
+            is_spectator: true,
+        }
+    }
+
+    /// Starts forwarding public game messages to a spectator.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_game` - A subscription to messages broadcast to all players
+    pub fn spectate(&mut self, from_game: broadcast::Receiver<Message>) {
+        // A spectator that lags behind skips the missed messages. They're still in the spectator snapshot.
+        let from_game = from_game.filter_map(|message| future::ready(message.ok()));
+        self.spawn_from_game_task(Box::pin(from_game));
+    }
+
     /// Sends a message directly to the player
     ///
     /// # Arguments
@@ -137,6 +174,7 @@ impl PlayerClient {
         let mut to_game = self.to_game.clone();
         let mut to_lobby = self.to_lobby.clone();
         let client_id = self.client_id.clone();
+        let is_spectator = self.is_spectator;
         let outgoing_to_client_future = Abortable::new(
             async move {
                 while let Some(incoming_msg) = from_client.next().await {
@@ -181,6 +219,21 @@ impl PlayerClient {
                     };
 
                     match incoming_msg {
+                        IncomingMessage::StartGame
+                        | IncomingMessage::GameCommand(_)
+                        | IncomingMessage::PlayerFocusChange(_)
+                        | IncomingMessage::PauseGame
+                        | IncomingMessage::ResumeGame
+                        | IncomingMessage::ReplacePlayer { .. }
+                        | IncomingMessage::AddBot
+                        | IncomingMessage::RemoveBot(_)
+                            if is_spectator =>
+                        {
+                            log::warn!(
+                                "Spectator {} attempted to act in the game, ignoring.",
+                                client_id
+                            );
+                        }
                         IncomingMessage::Ping => {
                             let _ = to_lobby
                                 .send((
@@ -214,6 +267,59 @@ impl PlayerClient {
                         IncomingMessage::StartGame => {
                             let _ = to_lobby.send((LobbyCommand::StartGame, None)).await;
                         }
+                        IncomingMessage::PauseGame => {
+                            let _ = to_lobby
+                                .send((
+                                    LobbyCommand::PauseGame {
+                                        client_id: client_id.clone(),
+                                    },
+                                    None,
+                                ))
+                                .await;
+                        }
+                        IncomingMessage::ResumeGame => {
+                            let _ = to_lobby
+                                .send((
+                                    LobbyCommand::ResumeGame {
+                                        client_id: client_id.clone(),
+                                    },
+                                    None,
+                                ))
+                                .await;
+                        }
+                        IncomingMessage::ReplacePlayer { seat, replacement } => {
+                            let _ = to_lobby
+                                .send((
+                                    LobbyCommand::ReplacePlayer {
+                                        client_id: client_id.clone(),
+                                        seat,
+                                        replacement,
+                                    },
+                                    None,
+                                ))
+                                .await;
+                        }
+                        IncomingMessage::AddBot => {
+                            let _ = to_lobby
+                                .send((
+                                    LobbyCommand::AddBot {
+                                        client_id: client_id.clone(),
+                                    },
+                                    None,
+                                ))
+                                .await;
+                        }
+                        IncomingMessage::RemoveBot(display_name) => {
+                            let _ = to_lobby
+                                .send((
+                                    LobbyCommand::RemoveBot {
+                                        client_id: client_id.clone(),
+                                        display_name,
+                                    },
+                                    None,
+                                ))
+                                .await;
+                        }
                         IncomingMessage::GameCommand(cmd) => {
                             let _ = to_game.send(cmd).await;
                         }
@@ -260,7 +366,10 @@ impl PlayerClient {
     /// # Arguments
     ///
     /// `from_game` - The channel from the game to the player
-    fn spawn_from_game_task(&mut self, mut from_game: Receiver<Message>) {
+    fn spawn_from_game_task<S>(&mut self, mut from_game: S)
+    where
+        S: Stream<Item = Message> + Send + Unpin + 'static,
+    {
         log::debug!("Creating from_game task for client {}.", self.client_id);
         // Task to manage messages from the game.
         let mut game_to_outbound_task = self.to_outbound_task.clone();
@@ -269,7 +378,7 @@ impl PlayerClient {
         let (abort_handle, abort_registration) = AbortHandle::new_pair();
         let future = Abortable::new(
             async move {
-                while let Some(game_msg) = from_game.recv().await {
+                while let Some(game_msg) = from_game.next().await {
                     log::debug!(
                         "Received game message {:?} from client {}.",
                         game_msg,
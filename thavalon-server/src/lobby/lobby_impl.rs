@@ -1,10 +1,12 @@
 use super::client::PlayerClient;
-use super::{LobbyState, OutgoingMessage};
 use super::{LobbyChannel, LobbyCommand, LobbyError, LobbyResponse, ResponseChannel};
+use super::{LobbyState, OutgoingMessage};
 use crate::database::games::{DBGameError, DatabaseGame};
 use crate::game::{
-    builder::GameBuilder,
-    snapshot::{Snapshots},
+    bot::Bot,
+    builder::{GameBuilder, GameHandle},
+    snapshot::Snapshots,
+    GameControl, Ruleset,
 };
 use crate::utils;
 
@@ -16,10 +18,13 @@ use tokio::{
 };
 use warp::filters::ws::WebSocket;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 const MAX_NUM_PLAYERS: usize = 10;
 
+/// Prefix for the player IDs of bots, so they can be told apart from real players in the database.
+const BOT_ID_PREFIX: &str = "bot:";
+
 /// A lobby for an individual game. The Lobby acts as an interface between the
 /// Thavalon game instance, the DatabaseGame which keeps the game state in sync
 /// with the database, and all players connected to the game.
@@ -30,12 +35,21 @@ pub struct Lobby {
     player_ids_to_client_ids: HashMap<String, String>,
     // Map of client IDs to player ID and display name.
     client_ids_to_player_info: HashMap<String, (String, String)>,
+    // Map of spectator client IDs to player ID and display name. Spectators are also in clients.
+    spectators: HashMap<String, (String, String)>,
+    // Map of bot display names to their player IDs. Bots don't have clients.
+    bots: HashMap<String, String>,
     clients: HashMap<String, PlayerClient>,
     status: LobbyState,
     builder: Option<GameBuilder>,
     snapshots: Option<Snapshots>,
     game_abort_handle: Option<AbortHandle>,
+    // Handle for managing the running game. This is None until the game starts.
+    game: Option<GameHandle>,
+    // Display names of seats in the running game that the next new player to join will take over.
+    open_seats: HashSet<String>,
     to_lobby: LobbyChannel,
+    ruleset: Ruleset,
 }
 
 impl Lobby {
@@ -44,16 +58,22 @@ impl Lobby {
     /// # Arguments
     ///
     /// * `end_game_channel` A channel this lobby should publish to when it's finished running.
+    /// * `host` The player ID of the lobby's creator, who manages the lobby and game.
+    /// * `ruleset` The house rules chosen by the lobby's creator.
     ///
     /// # Returns
     ///
     /// * `LobbyChannel` A channel for sending messages to this lobby.
-    pub async fn new(game_over_channel: oneshot::Sender<bool>) -> LobbyChannel {
+    pub async fn new(
+        game_over_channel: oneshot::Sender<bool>,
+        host: String,
+        ruleset: Ruleset,
+    ) -> LobbyChannel {
         let (tx, rx) = mpsc::channel(10);
 
         let to_lobby = tx.clone();
         task::spawn(async move {
-            let database_game = DatabaseGame::new().await.unwrap();
+            let database_game = DatabaseGame::new(host).await.unwrap();
             let friend_code = database_game.get_friend_code().clone();
             let lobby = Lobby {
                 game_over_channel: Some(game_over_channel),
@@ -61,12 +81,17 @@ impl Lobby {
                 friend_code,
                 player_ids_to_client_ids: HashMap::with_capacity(MAX_NUM_PLAYERS),
                 client_ids_to_player_info: HashMap::with_capacity(MAX_NUM_PLAYERS),
+                spectators: HashMap::new(),
+                bots: HashMap::new(),
                 clients: HashMap::with_capacity(MAX_NUM_PLAYERS),
                 status: LobbyState::Lobby,
                 builder: Some(GameBuilder::new()),
                 snapshots: None,
                 game_abort_handle: None,
+                game: None,
+                open_seats: HashSet::new(),
                 to_lobby,
+                ruleset,
             };
             lobby.listen(rx).await
         });
@@ -74,6 +99,124 @@ impl Lobby {
         tx
     }
 
+    /// Recreates the lobby for a game that was in progress when the server stopped, resuming the game from its
+    /// recorded history. Players rejoin through the usual reconnect path when they join with their old display name.
+    ///
+    /// # Arguments
+    ///
+    /// * `end_game_channel` A channel this lobby should publish to when it's finished running.
+    /// * `database_game` The in-progress game to restore.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(LobbyChannel)` A channel for sending messages to the restored lobby.
+    /// * `None` if the game could not be restored. The database game is ended in this case.
+    pub async fn restore(
+        game_over_channel: oneshot::Sender<bool>,
+        mut database_game: DatabaseGame,
+    ) -> Option<LobbyChannel> {
+        let friend_code = database_game.get_friend_code().clone();
+        log::info!("Attempting to restore game {}.", friend_code);
+        let history = match database_game.take_history() {
+            Some(history) => history,
+            None => {
+                log::error!("Game {} has no history to restore from.", friend_code);
+                let _ = database_game.end_game().await;
+                return None;
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(10);
+        let mut builder = GameBuilder::new();
+        let mut player_ids_to_client_ids = HashMap::with_capacity(MAX_NUM_PLAYERS);
+        let mut client_ids_to_player_info = HashMap::with_capacity(MAX_NUM_PLAYERS);
+        let mut clients = HashMap::with_capacity(MAX_NUM_PLAYERS);
+        let mut bots = HashMap::new();
+        for (player_id, display_name) in database_game.get_players_to_display_names() {
+            // Bots lost everything they knew, so catch them up on the game so far.
+            if player_id.starts_with(BOT_ID_PREFIX) {
+                let mut bot = Bot::new(display_name.clone(), rand::random());
+                match history.messages_for(display_name) {
+                    Ok(messages) => bot.restore(messages),
+                    Err(e) => {
+                        log::error!("Error restoring game {}: {}", friend_code, e);
+                        let _ = database_game.end_game().await;
+                        return None;
+                    }
+                }
+                builder.add_bot(bot);
+                bots.insert(display_name.clone(), player_id.clone());
+                continue;
+            }
+
+            let (sender, receiver) = builder.add_player(display_name.clone());
+            let client_id = utils::generate_random_string(32, false);
+            let client = PlayerClient::new(client_id.clone(), tx.clone(), sender, receiver);
+            player_ids_to_client_ids.insert(player_id.clone(), client_id.clone());
+            client_ids_to_player_info
+                .insert(client_id.clone(), (player_id.clone(), display_name.clone()));
+            clients.insert(client_id, client);
+        }
+
+        // Every player in the game must be back in the builder, otherwise their snapshots can't be rebuilt.
+        if history
+            .setup
+            .players
+            .iter()
+            .any(|player| !builder.get_player_list().contains(player))
+        {
+            log::error!(
+                "Game {} is missing players needed to restore it.",
+                friend_code
+            );
+            let _ = database_game.end_game().await;
+            return None;
+        }
+
+        let ruleset = history.setup.ruleset.clone();
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let recorder = database_game.history_recorder();
+        let (snapshots, game) =
+            match builder.resume(history, recorder, tx.clone(), abort_registration) {
+                Ok((snapshots, game, _)) => (snapshots, game),
+                Err(e) => {
+                    log::error!("Error restoring game {}: {}", friend_code, e);
+                    let _ = database_game.end_game().await;
+                    return None;
+                }
+            };
+
+        let lobby = Lobby {
+            game_over_channel: Some(game_over_channel),
+            database_game,
+            friend_code,
+            player_ids_to_client_ids,
+            client_ids_to_player_info,
+            spectators: HashMap::new(),
+            bots,
+            clients,
+            status: LobbyState::Game,
+            builder: None,
+            snapshots: Some(snapshots),
+            game_abort_handle: Some(abort_handle),
+            game: Some(game),
+            open_seats: HashSet::new(),
+            to_lobby: tx.clone(),
+            ruleset,
+        };
+        task::spawn(lobby.listen(rx));
+        Some(tx)
+    }
+
+    /// Checks whether `client_id` belongs to the lobby's host.
+    fn is_host(&self, client_id: &str) -> bool {
+        is_seated_host(
+            &self.client_ids_to_player_info,
+            client_id,
+            self.database_game.get_host(),
+        )
+    }
+
     /// Gets the friend code for the lobby in question.
     fn get_friend_code(&self) -> LobbyResponse {
         LobbyResponse::FriendCode(self.friend_code.clone())
@@ -93,6 +236,11 @@ impl Lobby {
             return self.reconnect_player(&player_id, &display_name);
         }
 
+        // New players can take over a seat that was opened for them in a running game.
+        if self.status == LobbyState::Game && !self.open_seats.is_empty() {
+            return self.claim_open_seat(player_id, display_name).await;
+        }
+
         // Unlike reconnecting, new players may only join when the game is in Lobby.
         if self.status != LobbyState::Lobby {
             log::warn!(
@@ -186,6 +334,363 @@ impl Lobby {
         return LobbyResponse::JoinGame(Ok(client_id));
     }
 
+    /// Gives a new player one of the open seats in the running game. Helper for add_player.
+    async fn claim_open_seat(&mut self, player_id: String, display_name: String) -> LobbyResponse {
+        if self.spectators.values().any(|(id, _)| id == &player_id) {
+            log::warn!(
+                "Spectator {} attempted to join game {} by friend code. Spectators must be given a seat.",
+                player_id,
+                self.friend_code
+            );
+            return LobbyResponse::JoinGame(Err(LobbyError::DuplicatePlayerError));
+        }
+
+        let seat = self.open_seats.iter().min().unwrap().clone();
+        let client_id = utils::generate_random_string(32, false);
+        match self
+            .hand_over_seat(&seat, player_id, client_id.clone(), display_name)
+            .await
+        {
+            Ok(()) => LobbyResponse::JoinGame(Ok(client_id)),
+            Err(e) => LobbyResponse::JoinGame(Err(e)),
+        }
+    }
+
+    /// Hands a player's seat in the running game to someone else, for example if they left for good. The seat goes
+    /// to the spectator named `replacement`, or if that's `None`, to the next new player who joins with the friend
+    /// code. Only the host can replace players.
+    async fn replace_player(
+        &mut self,
+        client_id: String,
+        seat: String,
+        replacement: Option<String>,
+    ) -> LobbyResponse {
+        if !self.is_host(&client_id) {
+            log::warn!(
+                "Client {} attempted to replace {} in game {} but is not the host.",
+                client_id,
+                seat,
+                self.friend_code
+            );
+            return LobbyResponse::Standard(Err(LobbyError::NotHost));
+        }
+
+        if self.status != LobbyState::Game {
+            log::warn!(
+                "Attempted to replace {} in game {}, which is not in progress.",
+                seat,
+                self.friend_code
+            );
+            return LobbyResponse::Standard(Err(LobbyError::InvalidStateError));
+        }
+
+        // Bots never leave, so only players with clients can be replaced.
+        if !self
+            .client_ids_to_player_info
+            .values()
+            .any(|(_, display_name)| display_name == &seat)
+        {
+            log::warn!(
+                "No player named {} in game {} to replace.",
+                seat,
+                self.friend_code
+            );
+            return LobbyResponse::Standard(Err(LobbyError::NoSuchPlayer));
+        }
+
+        let replacement = match replacement {
+            Some(replacement) => replacement,
+            None => {
+                log::info!(
+                    "Opening {}'s seat in game {} to the next player to join.",
+                    seat,
+                    self.friend_code
+                );
+                self.open_seats.insert(seat);
+                return LobbyResponse::Standard(Ok(()));
+            }
+        };
+
+        let spectator = self
+            .spectators
+            .iter()
+            .find(|(_, (_, display_name))| display_name == &replacement)
+            .map(|(client_id, (player_id, _))| (client_id.clone(), player_id.clone()));
+        let (client_id, player_id) = match spectator {
+            Some(spectator) => spectator,
+            None => {
+                log::warn!(
+                    "No spectator named {} in game {} to take {}'s seat.",
+                    replacement,
+                    self.friend_code,
+                    seat
+                );
+                return LobbyResponse::Standard(Err(LobbyError::NoSuchPlayer));
+            }
+        };
+
+        // The spectator keeps their client ID, so they only need to reconnect to start playing.
+        if let Err(e) = self
+            .hand_over_seat(&seat, player_id, client_id.clone(), replacement)
+            .await
+        {
+            return LobbyResponse::Standard(Err(e));
+        }
+        self.spectators.remove(&client_id);
+        self.on_spectator_list_change().await;
+        LobbyResponse::Standard(Ok(()))
+    }
+
+    /// Moves `seat` from the player in it to a new player, tearing down the old player's client. The new player's
+    /// client replaces any existing client with the same ID, and they play under the seat's display name.
+    async fn hand_over_seat(
+        &mut self,
+        seat: &str,
+        player_id: String,
+        client_id: String,
+        replacement: String,
+    ) -> Result<(), LobbyError> {
+        let (old_client_id, old_player_id) = self
+            .client_ids_to_player_info
+            .iter()
+            .find(|(_, (_, display_name))| display_name == seat)
+            .map(|(client_id, (player_id, _))| (client_id.clone(), player_id.clone()))
+            .ok_or(LobbyError::NoSuchPlayer)?;
+        log::info!(
+            "Handing {}'s seat in game {} from player {} to player {}.",
+            seat,
+            self.friend_code,
+            old_player_id,
+            player_id
+        );
+
+        let (sender, receiver) = match self
+            .game
+            .as_mut()
+            .unwrap()
+            .replace_player(seat.to_string())
+            .await
+        {
+            Ok(channels) => channels,
+            Err(e) => {
+                log::error!(
+                    "Error while replacing player {} in game {}. {}",
+                    old_player_id,
+                    self.friend_code,
+                    e
+                );
+                return Err(LobbyError::UnknownError);
+            }
+        };
+
+        // The old player's channels are closed now, so if the new player can't be saved, the seat goes back to the
+        // old player on the new channels. They keep their client ID, so they only need to reconnect.
+        if let Err(e) = self
+            .database_game
+            .replace_player(&old_player_id, player_id.clone())
+            .await
+        {
+            log::error!(
+                "Error while replacing player {} in game {}. {}",
+                old_player_id,
+                self.friend_code,
+                e
+            );
+            let client = PlayerClient::new(
+                old_client_id.clone(),
+                self.to_lobby.clone(),
+                sender,
+                receiver,
+            );
+            self.clients.insert(old_client_id, client);
+            return Err(LobbyError::DatabaseError);
+        }
+
+        self.clients.remove(&old_client_id);
+        self.client_ids_to_player_info.remove(&old_client_id);
+        self.player_ids_to_client_ids.remove(&old_player_id);
+
+        let client = PlayerClient::new(client_id.clone(), self.to_lobby.clone(), sender, receiver);
+        self.player_ids_to_client_ids
+            .insert(player_id.clone(), client_id.clone());
+        self.client_ids_to_player_info
+            .insert(client_id.clone(), (player_id, seat.to_string()));
+        self.clients.insert(client_id, client);
+        self.broadcast_message(&OutgoingMessage::PlayerReplaced {
+            seat: seat.to_string(),
+            replacement,
+        })
+        .await;
+        self.open_seats.remove(seat);
+        Ok(())
+    }
+
+    /// Adds a bot to the lobby, named after the first free "Bot N" display name. Bots can only be added by the host,
+    /// before the game starts.
+    async fn add_bot(&mut self, client_id: String) -> LobbyResponse {
+        if !self.is_host(&client_id) {
+            log::warn!(
+                "Client {} attempted to add a bot to game {} but is not the host.",
+                client_id,
+                self.friend_code
+            );
+            return LobbyResponse::Standard(Err(LobbyError::NotHost));
+        }
+
+        if self.status != LobbyState::Lobby {
+            log::warn!(
+                "Attempted to add a bot to in-progress or finished game {}.",
+                self.friend_code
+            );
+            return LobbyResponse::Standard(Err(LobbyError::InvalidStateError));
+        }
+
+        let players = self.builder.as_ref().unwrap().get_player_list();
+        let display_name = (1..)
+            .map(|n| format!("Bot {}", n))
+            .find(|name| !players.contains(name))
+            .unwrap();
+        let player_id = format!(
+            "{}{}",
+            BOT_ID_PREFIX,
+            utils::generate_random_string(32, false)
+        );
+        log::info!("Adding bot {} to lobby {}.", display_name, self.friend_code);
+
+        if let Err(e) = self
+            .database_game
+            .add_player(player_id.clone(), display_name.clone())
+            .await
+        {
+            log::error!(
+                "Error while adding bot {} to game {}. {}",
+                display_name,
+                self.friend_code,
+                e
+            );
+            return LobbyResponse::Standard(Err(LobbyError::DatabaseError));
+        }
+
+        self.builder
+            .as_mut()
+            .unwrap()
+            .add_bot(Bot::new(display_name.clone(), rand::random()));
+        self.bots.insert(display_name, player_id);
+        self.on_player_list_change().await;
+        LobbyResponse::Standard(Ok(()))
+    }
+
+    /// Removes a bot from the lobby. Like adding bots, only the host can do this, before the game starts.
+    async fn remove_bot(&mut self, client_id: String, display_name: String) -> LobbyResponse {
+        if !self.is_host(&client_id) {
+            log::warn!(
+                "Client {} attempted to remove bot {} from game {} but is not the host.",
+                client_id,
+                display_name,
+                self.friend_code
+            );
+            return LobbyResponse::Standard(Err(LobbyError::NotHost));
+        }
+
+        if self.status != LobbyState::Lobby {
+            return LobbyResponse::Standard(Err(LobbyError::InvalidStateError));
+        }
+
+        let player_id = match self.bots.remove(&display_name) {
+            Some(player_id) => player_id,
+            None => {
+                log::warn!(
+                    "No bot named {} in game {}.",
+                    display_name,
+                    self.friend_code
+                );
+                return LobbyResponse::Standard(Err(LobbyError::UnknownError));
+            }
+        };
+
+        log::info!(
+            "Removing bot {} from lobby {}.",
+            display_name,
+            self.friend_code
+        );
+        if let Err(e) = self.database_game.remove_player(&player_id).await {
+            log::error!(
+                "Error while removing bot {} from game {}. {}",
+                display_name,
+                self.friend_code,
+                e
+            );
+            return LobbyResponse::Standard(Err(LobbyError::DatabaseError));
+        }
+        self.builder.as_mut().unwrap().remove_player(&display_name);
+        self.on_player_list_change().await;
+        LobbyResponse::Standard(Ok(()))
+    }
+
+    /// Adds a spectator to the lobby. Spectators can join at any time before the game is finished, and only see
+    /// public game messages. A spectator joining again with the same player ID is reconnected.
+    async fn add_spectator(&mut self, player_id: String, display_name: String) -> LobbyResponse {
+        log::info!(
+            "Attempting to add spectator {} to lobby {}.",
+            player_id,
+            self.friend_code
+        );
+
+        if self.player_ids_to_client_ids.contains_key(&player_id) {
+            log::warn!(
+                "Player {} attempted to spectate their own game {}.",
+                player_id,
+                self.friend_code
+            );
+            return LobbyResponse::JoinGame(Err(LobbyError::DuplicatePlayerError));
+        }
+
+        for (client_id, (spectator_id, spectator_name)) in self.spectators.iter() {
+            if spectator_id == &player_id {
+                log::info!(
+                    "Spectator {} is already in game {}, reconnecting.",
+                    player_id,
+                    self.friend_code
+                );
+                return LobbyResponse::JoinGame(Ok(client_id.clone()));
+            }
+            if spectator_name == &display_name {
+                return LobbyResponse::JoinGame(Err(LobbyError::DuplicateDisplayName));
+            }
+        }
+
+        let client_id = utils::generate_random_string(32, false);
+        let mut client = PlayerClient::new_spectator(client_id.clone(), self.to_lobby.clone());
+        // If the game already started, start watching it right away. Otherwise, this happens in start_game.
+        if let Some(snapshots) = &self.snapshots {
+            client.spectate(snapshots.spectate());
+        }
+        log::info!(
+            "Successfully added spectator {} to game {} with unique client ID {}.",
+            player_id,
+            self.friend_code,
+            client_id
+        );
+        self.spectators
+            .insert(client_id.clone(), (player_id, display_name));
+        self.clients.insert(client_id.clone(), client);
+        self.on_spectator_list_change().await;
+        LobbyResponse::JoinGame(Ok(client_id))
+    }
+
+    /// Removes a spectator from the lobby.
+    async fn remove_spectator(&mut self, client_id: String) {
+        if let Some((player_id, _)) = self.spectators.remove(&client_id) {
+            log::info!(
+                "Removing spectator {} from game {}.",
+                player_id,
+                self.friend_code
+            );
+            self.clients.remove(&client_id);
+            self.on_spectator_list_change().await;
+        }
+    }
+
     /// Removes a player from the lobby and game.
     async fn remove_player(&mut self, client_id: String) {
         log::info!(
@@ -243,6 +748,7 @@ impl Lobby {
 
         client.update_websocket(ws).await;
         self.on_player_list_change().await;
+        self.on_spectator_list_change().await;
         LobbyResponse::Standard(Ok(()))
     }
 
@@ -270,9 +776,24 @@ impl Lobby {
             let current_players = self.builder.as_ref().unwrap().get_player_list();
             self.broadcast_message(&OutgoingMessage::PlayerList(current_players.to_vec()))
                 .await;
+            // Resend the house rules so newly-joined players can see them before the game starts.
+            self.broadcast_message(&OutgoingMessage::Ruleset(self.ruleset.clone()))
+                .await;
         }
     }
 
+    /// Handles a change to the spectator list. Unlike players, spectators can come and go at any time.
+    async fn on_spectator_list_change(&mut self) {
+        let mut spectators = self
+            .spectators
+            .values()
+            .map(|(_, display_name)| display_name.clone())
+            .collect::<Vec<_>>();
+        spectators.sort();
+        self.broadcast_message(&OutgoingMessage::SpectatorList(spectators))
+            .await;
+    }
+
     // Handles dealing with a disconnected player.
     // If the lobby isn't in progress or done, a disconnect should remove the player.
     // Otherwise, nothing happens.
@@ -283,6 +804,12 @@ impl Lobby {
             self.friend_code
         );
 
+        // Spectators always leave when they disconnect, since they can rejoin at any time.
+        if self.spectators.contains_key(&client_id) {
+            self.remove_spectator(client_id).await;
+            return LobbyResponse::Standard(Ok(()));
+        }
+
         // If we're in the lobby phase, a disconnect counts as leaving the game.
         if self.status == LobbyState::Lobby {
             self.remove_player(client_id).await;
@@ -293,6 +820,18 @@ impl Lobby {
 
     /// Starts the game and updates statuses
     async fn start_game(&mut self) -> LobbyResponse {
+        // Check the house rules against the final player count before committing to starting the game.
+        let player_count = self.builder.as_ref().unwrap().get_player_list().len();
+        if let Err(e) = self.ruleset.to_spec(player_count) {
+            log::warn!(
+                "Cannot start game {} with {} players. {}",
+                self.friend_code,
+                player_count,
+                e
+            );
+            return LobbyResponse::Standard(Err(LobbyError::InvalidRuleset(e)));
+        }
+
         // The only thing that can fail is updating the database. In this case,
         // the lobby is probably dead, so panic to blow up everything.
         if let Err(e) = self.database_game.start_game().await {
@@ -303,9 +842,20 @@ impl Lobby {
         let builder = self.builder.take().unwrap();
         let (abort_handle, abort_registration) = AbortHandle::new_pair();
         self.game_abort_handle = Some(abort_handle);
-        match builder.start(self.to_lobby.clone(), abort_registration) {
-            Ok((snapshots, _)) => {
+        let recorder = self.database_game.history_recorder();
+        match builder.start(
+            &self.ruleset,
+            recorder,
+            self.to_lobby.clone(),
+            abort_registration,
+        ) {
+            Ok((snapshots, game, _)) => {
+                for client_id in self.spectators.keys() {
+                    let client = self.clients.get_mut(client_id).unwrap();
+                    client.spectate(snapshots.spectate());
+                }
                 self.snapshots = Some(snapshots);
+                self.game = Some(game);
                 // Tell the players the game is about to start to move to the game page.
                 self.broadcast_message(&OutgoingMessage::LobbyState(LobbyState::Game))
                     .await;
@@ -321,8 +871,53 @@ impl Lobby {
         }
     }
 
+    /// Pauses or resumes the running game. Only the host can do this. The game itself announces the change to players.
+    async fn control_game(&mut self, client_id: String, control: GameControl) -> LobbyResponse {
+        if !self.is_host(&client_id) {
+            log::warn!(
+                "Client {} attempted to {:?} game {} but is not the host.",
+                client_id,
+                control,
+                self.friend_code
+            );
+            return LobbyResponse::Standard(Err(LobbyError::NotHost));
+        }
+
+        let game = match (&self.status, self.game.as_mut()) {
+            (LobbyState::Game, Some(game)) => game,
+            _ => {
+                log::warn!(
+                    "Attempted to {:?} game {}, which is not in progress.",
+                    control,
+                    self.friend_code
+                );
+                return LobbyResponse::Standard(Err(LobbyError::InvalidStateError));
+            }
+        };
+
+        log::info!("Sending {:?} to game {}.", control, self.friend_code);
+        if let Err(e) = game.control(control).await {
+            log::error!("Could not control game {}. {}", self.friend_code, e);
+            return LobbyResponse::Standard(Err(LobbyError::UnknownError));
+        }
+        LobbyResponse::Standard(Ok(()))
+    }
+
     // End the lobby, including ending the database game and aborting the game thread.
     async fn end_game(&mut self) -> LobbyResponse {
+        // Spectators can't ask for anything once the lobby is finished, so reveal everything to them now if allowed.
+        if self.ruleset.omniscient_spectators() {
+            if let Some(snapshots) = &self.snapshots {
+                let snapshot = snapshots.spectator_snapshot(true);
+                let message = OutgoingMessage::SpectatorSnapshot(snapshot);
+                let message = serde_json::to_string(&message).unwrap();
+                for client_id in self.spectators.keys() {
+                    let client = self.clients.get_mut(client_id).unwrap();
+                    client.send_message(message.clone()).await;
+                }
+            }
+        }
+
         self.status = LobbyState::Finished;
         self.database_game
             .end_game()
@@ -361,6 +956,10 @@ impl Lobby {
 
     /// Gets all snapshots that have occurred for a given client ID.
     async fn get_snapshots(&mut self, client_id: String) -> LobbyResponse {
+        if self.spectators.contains_key(&client_id) {
+            return self.get_spectator_snapshot(client_id).await;
+        }
+
         let (_, display_name) = &self.client_ids_to_player_info[&client_id];
         let snapshot = self
             .snapshots
@@ -372,7 +971,20 @@ impl Lobby {
             .unwrap()
             .clone();
         let client = self.clients.get_mut(&client_id).unwrap();
-        let message = OutgoingMessage::Snapshot(snapshot);
+        let message = OutgoingMessage::Snapshot(Box::new(snapshot));
+        let message = serde_json::to_string(&message).unwrap();
+        client.send_message(message).await;
+        LobbyResponse::None
+    }
+
+    /// Sends a spectator-safe snapshot to a spectator. Nothing is sent if the game hasn't started.
+    async fn get_spectator_snapshot(&mut self, client_id: String) -> LobbyResponse {
+        let snapshot = match &self.snapshots {
+            Some(snapshots) => snapshots.spectator_snapshot(self.ruleset.omniscient_spectators()),
+            None => return LobbyResponse::None,
+        };
+        let client = self.clients.get_mut(&client_id).unwrap();
+        let message = OutgoingMessage::SpectatorSnapshot(snapshot);
         let message = serde_json::to_string(&message).unwrap();
         client.send_message(message).await;
         LobbyResponse::None
@@ -417,6 +1029,10 @@ impl Lobby {
                     player_id,
                     display_name,
                 } => self.add_player(player_id, display_name).await,
+                LobbyCommand::AddSpectator {
+                    player_id,
+                    display_name,
+                } => self.add_spectator(player_id, display_name).await,
 
                 LobbyCommand::GetFriendCode => self.get_friend_code(),
                 LobbyCommand::IsClientRegistered { client_id } => {
@@ -431,6 +1047,22 @@ impl Lobby {
                 }
                 LobbyCommand::StartGame => self.start_game().await,
                 LobbyCommand::EndGame => self.end_game().await,
+                LobbyCommand::PauseGame { client_id } => {
+                    self.control_game(client_id, GameControl::Pause).await
+                }
+                LobbyCommand::ResumeGame { client_id } => {
+                    self.control_game(client_id, GameControl::Resume).await
+                }
+                LobbyCommand::ReplacePlayer {
+                    client_id,
+                    seat,
+                    replacement,
+                } => self.replace_player(client_id, seat, replacement).await,
+                LobbyCommand::AddBot { client_id } => self.add_bot(client_id).await,
+                LobbyCommand::RemoveBot {
+                    client_id,
+                    display_name,
+                } => self.remove_bot(client_id, display_name).await,
                 LobbyCommand::PlayerDisconnect { client_id } => {
                     self.on_player_disconnect(client_id).await
                 }
@@ -450,3 +1082,45 @@ impl Lobby {
         }
     }
 }
+
+/// Checks whether `client_id` is seated in `players` as `host`. Spectators are never the host: whoever takes the host's
+/// seat takes over hosting, and spectator clients can't send host commands anyway.
+fn is_seated_host(
+    players: &HashMap<String, (String, String)>,
+    client_id: &str,
+    host: &str,
+) -> bool {
+    players
+        .get(client_id)
+        .is_some_and(|(player_id, _)| player_id == host)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::is_seated_host;
+
+    #[test]
+    fn test_is_seated_host() {
+        let players = vec![
+            ("client-a", "player-a", "Alice"),
+            ("client-b", "player-b", "Bob"),
+        ]
+        .into_iter()
+        .map(|(client_id, player_id, display_name)| {
+            (
+                client_id.to_string(),
+                (player_id.to_string(), display_name.to_string()),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+        assert!(is_seated_host(&players, "client-a", "player-a"));
+        assert!(!is_seated_host(&players, "client-b", "player-a"));
+        // Once the host's seat is handed over, their old client is no longer the host, even if they come back to
+        // spectate
+        assert!(!is_seated_host(&players, "client-c", "player-a"));
+        assert!(!is_seated_host(&players, "client-a", "player-c"));
+    }
+}
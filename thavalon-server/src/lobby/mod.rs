@@ -4,7 +4,10 @@
 mod client;
 mod lobby_impl;
 
-use crate::game::{snapshot::GameSnapshot, Action, Message};
+use crate::game::{
+    snapshot::{GameSnapshot, SpectatorSnapshot},
+    Action, CreateGameError, Message, Ruleset,
+};
 pub use lobby_impl::Lobby;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -35,6 +38,12 @@ pub enum LobbyError {
     NameChangeOnReconnectError,
     #[error("The display name is already in use.")]
     DuplicateDisplayName,
+    #[error("No player or spectator has that display name.")]
+    NoSuchPlayer,
+    #[error("Only the lobby's host can do that.")]
+    NotHost,
+    #[error("The lobby's ruleset cannot be used. {0}")]
+    InvalidRuleset(CreateGameError),
 }
 
 /// Enum of available commands to send to the lobby.
@@ -43,6 +52,10 @@ pub enum LobbyCommand {
         player_id: String,
         display_name: String,
     },
+    AddSpectator {
+        player_id: String,
+        display_name: String,
+    },
     GetFriendCode,
     IsClientRegistered {
         client_id: String,
@@ -59,6 +72,24 @@ pub enum LobbyCommand {
     },
     StartGame,
     EndGame,
+    PauseGame {
+        client_id: String,
+    },
+    ResumeGame {
+        client_id: String,
+    },
+    ReplacePlayer {
+        client_id: String,
+        seat: String,
+        replacement: Option<String>,
+    },
+    AddBot {
+        client_id: String,
+    },
+    RemoveBot {
+        client_id: String,
+        display_name: String,
+    },
     PlayerDisconnect {
         client_id: String,
     },
@@ -95,6 +126,14 @@ enum IncomingMessage {
     GetPlayerList,
     GetSnapshot,
     PlayerFocusChange(bool),
+    PauseGame,
+    ResumeGame,
+    ReplacePlayer {
+        seat: String,
+        replacement: Option<String>,
+    },
+    AddBot,
+    RemoveBot(String),
 }
 
 /// An outgoing message to the client.
@@ -105,11 +144,19 @@ pub enum OutgoingMessage {
     PlayerList(Vec<String>),
     LobbyState(LobbyState),
     GameMessage(Message),
-    Snapshot(GameSnapshot),
+    Snapshot(Box<GameSnapshot>),
+    SpectatorSnapshot(SpectatorSnapshot),
+    SpectatorList(Vec<String>),
+    Ruleset(Ruleset),
     PlayerFocusChange {
         displayName: String,
         isTabbedOut: bool,
     },
+    /// A player's seat was handed to someone else, who plays under the seat's display name from now on.
+    PlayerReplaced {
+        seat: String,
+        replacement: String,
+    },
 }
 
 #[derive(Serialize, Eq, PartialEq, Clone)]
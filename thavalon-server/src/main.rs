@@ -33,6 +33,16 @@ fn setup_logger() -> Result<(), fern::InitError> {
 
 #[tokio::main]
 async fn main() {
+    // `thavalon-server simulate ...` runs games between bots for balance analysis instead of starting the server.
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("simulate") {
+        if let Err(e) = game::simulation::run(&args[1..]) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     setup_logger().expect("Could not set up logging");
     database::initialize_mongo_client().await;
     connections::serve_connections().await;
@@ -1,6 +1,5 @@
 //! Module for utility functions that may be useful across the entire application.
 
-
 use rand::{distributions::Alphanumeric, Rng};
 
 use std::iter;
@@ -39,15 +39,26 @@ export enum GameMessageType {
     VoteRecieved = "voteReceived",
     VotingResults = "votingResults",
     MissionGoing = "missionGoing",
+    CardPlayed = "cardPlayed",
     MissionResults = "missionResults",
+    LoversRevealed = "loversRevealed",
     AgravaineDeclaration = "agravaineDeclaration",
     BeginAssassination = "beginAssassination",
     AssassinationResult = "assassinationResult",
+    EvilDiscussion = "evilDiscussion",
+    Nomination = "nomination",
+    DiscussionOver = "discussionOver",
     GameOver = "gameOver",
+    GameReveal = "gameReveal",
     Toast = "toast",
     ArthurCanDeclare = "arthurCanDeclare",
     ArthurCannotDeclare = "arthurCannotDeclare",
+    ObscureStatus = "obscureStatus",
     ArthurDeclaration = "arthurDeclaration",
+    TurnTimer = "turnTimer",
+    GamePaused = "gamePaused",
+    GameResumed = "gameResumed",
+    LegalActions = "legalActions",
 }
 
 /**
@@ -69,6 +80,8 @@ export enum GameActionType {
     Declare = "Declare",
     Assassinate = "Assassinate",
     MoveToAssassination = "MoveToAssassination",
+    Discuss = "Discuss",
+    Nominate = "Nominate",
 }
 
 /**
@@ -100,10 +113,65 @@ export interface RoleInfo {
 export interface Snapshot {
     me: string,
     roleInfo: RoleInfo,
-    missions: any[],
+    paused: boolean,
+    phase: SnapshotPhase,
+    proposalOrder: string[],
+    mission: number,
+    missionSize: number,
+    proposer?: string,
+    currentProposal: string[],
+    proposals: ProposalSummary[],
+    missions: MissionSummary[],
+    proposalsMade: number,
+    maxProposals: number,
+    force: boolean,
+    arthur: { canDeclare: boolean, declared?: string },
+    maeve?: { canObscure: boolean, obscuresRemaining: number },
+    pending: string[],
+    nominations: NominationMessage[],
+    legalActions: LegalAction[],
     log: GameMessage[],
 }
 
+/**
+ * The phase of the game, as far as players can tell.
+ */
+export type SnapshotPhase = "Starting" | "Proposing" | "Voting" | "OnMission" | "MissionComplete" | "Assassination" | "Done";
+
+/**
+ * A proposal from the snapshot, with how it was voted on.
+ */
+export interface ProposalSummary {
+    mission: number,
+    proposer: string,
+    players: string[],
+    votes?: VoteCounts,
+    weights: Record<string, number>,
+    sent: boolean,
+}
+
+/**
+ * The results of a mission from the snapshot.
+ */
+export interface MissionSummary {
+    mission: number,
+    players: string[],
+    successes: number,
+    fails: number,
+    reverses: number,
+    questingBeasts: number,
+    passed: boolean,
+    agravaine?: string,
+}
+
+/**
+ * Message sent to Maeve with whether she can obscure votes.
+ */
+export interface ObscureStatusMessage {
+    can_obscure: boolean,
+    obscures_remaining: number,
+}
+
 /**
  * The next proposal message sent from the server.
  */
@@ -156,6 +224,8 @@ export interface MissionGoingMessage {
 export interface VotingResultsMessage {
     sent: boolean
     counts: VoteCounts
+    /** How many votes each player cast, such as 2 for a declared Arthur */
+    weights: Record<string, number>
 }
 
 /**
@@ -193,11 +263,17 @@ export enum Role {
     Maelegant = "Maelegant",
     Maeve = "Maeve",
     Agravaine = "Agravaine",
-    Arthur = "Arthur"
+    Arthur = "Arthur",
+    Guinevere = "Guinevere",
+    Titania = "Titania",
 }
 
-/** Length of time Agravaine has to declare in seconds */
-export const AGRAVAINE_DECLARATION_TIME = 30;
+/**
+ * Interface for a Lovers reveal message, sent to Tristan and Iseult once they've been on a mission together.
+ */
+export interface LoversRevealedMessage {
+    lover: string,
+}
 
 /**
  * Interface for an Agravaine declaration message.
@@ -212,4 +288,120 @@ export interface AgravaineDeclarationMessage {
  */
 export interface ArthurDeclarationMessage {
     player: string,
-}
\ No newline at end of file
+}
+
+/**
+ * Interface for the post-game reveal, with everything that happened in the game.
+ */
+export interface GameRevealMessage {
+    winningTeam: "Good" | "Evil",
+    roles: { [player: string]: Role },
+    assassin: string,
+    priorityTarget: string,
+    proposals: ProposalRecord[],
+    missions: MissionRecord[],
+    assassination?: AssassinationRecord,
+}
+
+/**
+ * A proposal from the post-game reveal, including the real votes behind obscured proposals.
+ */
+export interface ProposalRecord {
+    mission: number,
+    proposer: string,
+    players: string[],
+    votes?: { [player: string]: boolean },
+    obscured: boolean,
+    sent: boolean,
+}
+
+/**
+ * A mission from the post-game reveal, including the card each player played.
+ */
+export interface MissionRecord {
+    mission: number,
+    proposal: number,
+    cards: { [player: string]: "Success" | "Fail" | "Reverse" },
+    questingBeasts: number,
+    passed: boolean,
+    agravaineWindow: boolean,
+    agravaineDeclaration?: string,
+}
+
+/**
+ * The assassination from the post-game reveal.
+ */
+export interface AssassinationRecord {
+    assassin: string,
+    players: string[],
+    target: string,
+    other?: Accusation,
+    correct: boolean,
+}
+
+/**
+ * Naming a player as a specific role during an assassination.
+ */
+export interface Accusation {
+    player: string,
+    role: Role,
+}
+
+/**
+ * Message sent to Evil players when a teammate says something while discussing the assassination.
+ */
+export interface EvilDiscussionMessage {
+    player: string,
+    message: string,
+}
+
+/**
+ * Message sent to Evil players when a teammate suggests assassination targets.
+ */
+export interface NominationMessage {
+    player: string,
+    players: string[],
+    target: string,
+    other?: Accusation,
+}
+
+/**
+ * Interface for a turn timer message, sent when a phase with a time limit starts.
+ */
+export interface TurnTimerMessage {
+    seconds: number,
+}
+
+/**
+ * Something the player is allowed to do right now. Assassinate and Nominate include who can be named, and as what.
+ */
+export interface LegalAction {
+    actionType: GameActionType,
+    size?: number,
+    players?: string[],
+    cards?: string[],
+    targets?: string[],
+    roles?: string[],
+    singlePick?: boolean,
+}
+
+/**
+ * Message sent to a player whenever what they're allowed to do changes, listing everything they can do right now.
+ */
+export interface LegalActionsMessage {
+    actions: LegalAction[],
+}
+
+/**
+ * Error message sent when the game rejects an action. The code is stable, so it can be used to pick what to show.
+ * Some errors include details, such as the expected proposal size or the player who couldn't be added.
+ */
+export interface GameActionErrorMessage {
+    code: string,
+    player?: string,
+    expected?: number,
+    actual?: number,
+    card?: string,
+    target?: string,
+    role?: string,
+}
@@ -1,7 +1,7 @@
 import React, { useEffect, useState } from "react";
 import ReactModal from "react-modal";
 import { GameSocket, InboundMessage, InboundMessageType } from "../../../utils/GameSocket";
-import { GameActionType, InteractionProps, MissionCard, Vote, MissionGoingMessage, GameMessage, GameMessageType, MissionResultsMessage, Role, AGRAVAINE_DECLARATION_TIME } from "../constants";
+import { GameActionType, InteractionProps, MissionCard, Vote, MissionGoingMessage, GameMessage, GameMessageType, MissionResultsMessage } from "../constants";
 import { createSelectedPlayerTypesList, sendGameAction } from "../gameUtils";
 import { PlayerCard } from "../playerCard";
 import { ListGroup } from "react-bootstrap";
@@ -33,15 +33,6 @@ interface MissionResultModalProps {
     agravaine?: string,
 }
 
-/**
- * Required properties for the AfterMissionMessage
- */
-interface AfterMissionMessageProps {
-    role: Role,
-    onMission: boolean,
-    submitAgravaineDeclaration: () => void,
-}
-
 /**
  * Component managing all of the mission related interactions.
  * @param props Required properties for the MissionManager
@@ -86,13 +77,6 @@ export function MissionManager(props: MissionManagerProps): JSX.Element {
         }
     }
 
-    /**
-     * Submits an Agravaine declaration to the server.
-     */
-    function submitAgravaineDeclaration(): void {
-        sendGameAction(GameActionType.Declare)
-    }
-
     // Create player cards here
     const playerCards = props.playerList.map((playerName) => {
         const selectedTypes = createSelectedPlayerTypesList(playerName, props.primarySelectedPlayers, props.secondarySelectedPlayers);
@@ -119,10 +103,7 @@ export function MissionManager(props: MissionManagerProps): JSX.Element {
                     <>Please wait for the mission cards to be played </>
                 }
                 {missionHasFinished &&
-                    <AfterMissionMessage
-                        onMission={onMission}
-                        role={props.role}
-                        submitAgravaineDeclaration={submitAgravaineDeclaration} />
+                    <div className="after-mission-message">The mission is over.</div>
                 }
             </div>
         </>
@@ -204,29 +185,3 @@ function MissionCardButtons(props: MissionCardProps): JSX.Element {
     )
 }
 
-/**
- * Renders an appropriate after mission message for the player.
- * For Agravaine, this will be a declare button or a message explaining why they
- * can't declare. For others, this is a countdown for Agravaine.
- * @param props Required properties for the AfterMissionMessage
- */
-function AfterMissionMessage(props: AfterMissionMessageProps): JSX.Element {
-    const [timeToDeclare, setTimeToDeclare] = useState(AGRAVAINE_DECLARATION_TIME);
-    useEffect(() => {
-        const timer = setTimeout(() => setTimeToDeclare(timeToDeclare - 1), 1000);
-        return () => clearTimeout(timer);
-    });
-
-    return (
-        <div className="after-mission-message">
-            {props.role !== Role.Agravaine && `Agravaine has ${ timeToDeclare } seconds to declare.`}
-            {props.role === Role.Agravaine && !props.onMission && "You cannot declare since you weren't on the mission. Do better."}
-            {props.role === Role.Agravaine && props.onMission &&
-                <button
-                    className="agravaine-declare-button"
-                    onClick={() => props.submitAgravaineDeclaration()}>
-                    Declare as Agravaine ({timeToDeclare}s left)
-            </button>}
-        </div>
-    );
-}
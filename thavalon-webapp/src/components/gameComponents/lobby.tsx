@@ -10,7 +10,7 @@ interface LobbyProps {
 }
 
 /**
- * Component listing players currently in the lobby and a button to start the game.
+ * Component listing players currently in the lobby, with buttons to add bots and start the game.
  */
 export function Lobby(props: LobbyProps): JSX.Element {
     // State for maintaining the player list.
@@ -54,6 +54,11 @@ export function Lobby(props: LobbyProps): JSX.Element {
             <ListGroup variant="flush">
                 {players}
             </ListGroup>
+            <Button
+                variant="secondary"
+                onClick={() => connection?.sendMessage({ messageType: OutboundMessageType.AddBot })}>
+                Add Bot
+            </Button>
             <Button
                 variant="primary"
                 onClick={() => connection?.sendMessage({ messageType: OutboundMessageType.StartGame })}>
@@ -1,6 +1,6 @@
 import React, { useCallback, useEffect, useState } from "react";
 import { GameSocket, InboundMessage, InboundMessageType, OutboundMessageType } from "../../utils/GameSocket";
-import { GameActionType, RoleInfo, Snapshot, GameMessage, GameMessageType, Role } from "./constants";
+import { GameActionType, RoleInfo, Snapshot, GameMessage, GameMessageType, Role, LegalAction, LegalActionsMessage } from "./constants";
 import "../../styles/gameStyles/roleInformation.scss";
 import { sendGameAction } from "./gameUtils";
 
@@ -10,6 +10,8 @@ import { sendGameAction } from "./gameUtils";
 export function RoleInformation(): JSX.Element {
     const [roleInfo, setRoleInfo] = useState<RoleInfo | undefined>(undefined);
     const [showDeclareButton, setShowDeclareButton] = useState(false);
+    // Agravaine can declare whenever the server says Declare is legal for them
+    const [agravaineCanDeclare, setAgravaineCanDeclare] = useState(false);
 
     /**
      * Handles any lobby messages that come from the server. If the message type
@@ -21,14 +23,17 @@ export function RoleInformation(): JSX.Element {
             case InboundMessageType.Snapshot: {
                 const snapshot = message.data as Snapshot;
                 setRoleInfo(snapshot.roleInfo);
+                setAgravaineCanDeclare(snapshot.roleInfo.role === Role.Agravaine && canDeclare(snapshot.legalActions));
                 break;
             }
             case InboundMessageType.GameMessage: {
-                // For now, only Arthur has a declare button here.
+                const gameMessage = message.data as GameMessage;
+                if (roleInfo?.role === Role.Agravaine && gameMessage.messageType === GameMessageType.LegalActions) {
+                    setAgravaineCanDeclare(canDeclare((gameMessage.data as LegalActionsMessage).actions));
+                }
                 if (roleInfo?.role !== Role.Arthur) {
                     return;
                 }
-                const gameMessage = message.data as GameMessage;
                 if (gameMessage.messageType === GameMessageType.ArthurCanDeclare) {
                     setShowDeclareButton(true);
                 } else if (gameMessage.messageType === GameMessageType.ArthurCannotDeclare) {
@@ -39,6 +44,22 @@ export function RoleInformation(): JSX.Element {
         }
     }
 
+    /**
+     * Whether a player with `actions` is allowed to declare.
+     * @param actions The player's legal actions
+     */
+    function canDeclare(actions: LegalAction[]): boolean {
+        return actions.some((action) => action.actionType === GameActionType.Declare);
+    }
+
+    /**
+     * Wrapper function that sends a "Declare" action for Agravaine.
+     */
+    function submitAgravaineDeclaration(): void {
+        sendGameAction(GameActionType.Declare);
+        setAgravaineCanDeclare(false);
+    }
+
     /**
      * Wrapper function that sends a "Declare" action for Arthur.
      */
@@ -106,6 +127,12 @@ export function RoleInformation(): JSX.Element {
 
                     Declare as Arthur
             </button>}
+            {agravaineCanDeclare &&
+                <button
+                    className="declare-button-evil"
+                    onClick={() => submitAgravaineDeclaration()}>
+                    Declare as Agravaine
+            </button>}
         </div>
     </div>
 
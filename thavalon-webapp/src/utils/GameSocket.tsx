@@ -8,7 +8,12 @@ export enum OutboundMessageType {
     StartGame = "StartGame",
     GetSnapshot = "GetSnapshot",
     PlayerFocusChange = "PlayerFocusChange",
-    GameCommand = "GameCommand"
+    GameCommand = "GameCommand",
+    AddBot = "AddBot",
+    RemoveBot = "RemoveBot",
+    PauseGame = "PauseGame",
+    ResumeGame = "ResumeGame",
+    ReplacePlayer = "ReplacePlayer"
 }
 
 export interface OutboundMessage {
@@ -22,7 +27,8 @@ export enum InboundMessageType {
     LobbyState = "LobbyState",
     GameMessage = "GameMessage",
     Snapshot = "Snapshot",
-    PlayerFocusChange = "PlayerFocusChange"
+    PlayerFocusChange = "PlayerFocusChange",
+    PlayerReplaced = "PlayerReplaced"
 }
 
 export interface InboundMessage {